@@ -0,0 +1,405 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Matching packages against a local clone of the [RustSec advisory database](https://github.com/rustsec/advisory-db).
+//!
+//! Every advisory is a Markdown file in `crates/<package>/` that starts with a TOML front matter
+//! fenced by ```` ```toml ````, followed by the title as a `#` heading and the description.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use cargo_metadata::semver::{Version, VersionReq};
+use serde::Deserialize;
+use thiserror::Error;
+
+/// A single advisory, as recorded in the database
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advisory {
+    /// The RustSec identifier, e.g. `RUSTSEC-2021-0001`
+    pub id: String,
+    /// The name of the affected crate on crates.io
+    pub package: String,
+    /// The date the advisory was reported, in the `YYYY-MM-DD` format
+    pub date: String,
+    /// The URL of the primary report, if any
+    pub url: Option<String>,
+    /// Identifiers of the same vulnerability in other databases, e.g. CVE or GHSA IDs
+    pub aliases: Vec<String>,
+    /// The CVSS vector string, e.g. `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`
+    pub cvss: Option<String>,
+    /// Additional URLs with information about the vulnerability
+    pub references: Vec<String>,
+    pub title: String,
+    pub description: String,
+    /// Versions in which the vulnerability has been fixed
+    pub patched: Vec<VersionReq>,
+    /// Versions which were never affected
+    pub unaffected: Vec<VersionReq>,
+}
+
+impl Advisory {
+    /// Returns `true` if the given version of the package is affected by the advisory
+    pub fn affects(&self, version: &Version) -> bool {
+        !self
+            .patched
+            .iter()
+            .chain(self.unaffected.iter())
+            .any(|req| req.matches(version))
+    }
+
+    /// The URL of the advisory on rustsec.org
+    pub fn rustsec_url(&self) -> String {
+        format!("https://rustsec.org/advisories/{}.html", self.id)
+    }
+}
+
+/// The advisories for crates published to crates.io, by the name of the crate
+#[derive(Debug, Clone, Default)]
+pub struct AdvisoryDatabase {
+    advisories: BTreeMap<String, Vec<Advisory>>,
+}
+
+impl AdvisoryDatabase {
+    /// Loads the advisories from a local clone of the database.
+    ///
+    /// Withdrawn advisories are skipped, and so are informational ones,
+    /// such as notices about unmaintained crates, since they do not describe vulnerabilities.
+    pub fn load(path: &Path) -> Result<Self, AdvisoryDbError> {
+        let crates_dir = path.join("crates");
+        let mut database = AdvisoryDatabase::default();
+        for crate_dir in read_dir(&crates_dir)? {
+            if !crate_dir.is_dir() {
+                continue;
+            }
+            for file in read_dir(&crate_dir)? {
+                if file.extension().map_or(true, |ext| ext != "md") {
+                    continue;
+                }
+                let contents =
+                    std::fs::read_to_string(&file).map_err(|error| AdvisoryDbError::IoError {
+                        path: file.clone(),
+                        error,
+                    })?;
+                let advisory = parse_advisory(&contents).map_err(|error| {
+                    AdvisoryDbError::InvalidAdvisory {
+                        path: file.clone(),
+                        error,
+                    }
+                })?;
+                if let Some(advisory) = advisory {
+                    database.insert(advisory);
+                }
+            }
+        }
+        log::debug!(
+            "Loaded {} advisories from {}",
+            database.advisories.values().map(Vec::len).sum::<usize>(),
+            path.display()
+        );
+        Ok(database)
+    }
+
+    pub fn insert(&mut self, advisory: Advisory) {
+        self.advisories
+            .entry(advisory.package.clone())
+            .or_default()
+            .push(advisory);
+    }
+
+    /// Returns the advisories affecting the given version of the crate from crates.io
+    pub fn find<'a>(
+        &'a self,
+        package: &str,
+        version: &Version,
+    ) -> impl Iterator<Item = &'a Advisory> + 'a {
+        let version = version.clone();
+        self.advisories
+            .get(package)
+            .into_iter()
+            .flatten()
+            .filter(move |advisory| advisory.affects(&version))
+    }
+}
+
+/// Lists the directory entries, sorted by path for reproducible output
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>, AdvisoryDbError> {
+    let io_error = |error| AdvisoryDbError::IoError {
+        path: dir.to_owned(),
+        error,
+    };
+    let mut entries = std::fs::read_dir(dir)
+        .map_err(io_error)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io_error)?;
+    entries.sort();
+    Ok(entries)
+}
+
+#[derive(Debug, Deserialize)]
+struct FrontMatter {
+    advisory: AdvisoryMetadata,
+    #[serde(default)]
+    versions: AdvisoryVersions,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdvisoryMetadata {
+    id: String,
+    package: String,
+    date: String,
+    url: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    cvss: Option<String>,
+    #[serde(default)]
+    references: Vec<String>,
+    informational: Option<String>,
+    withdrawn: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AdvisoryVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+    #[serde(default)]
+    unaffected: Vec<String>,
+}
+
+/// Parses an advisory file, returning `None` for withdrawn and informational advisories
+fn parse_advisory(contents: &str) -> Result<Option<Advisory>, String> {
+    let contents = contents.trim_start();
+    let front_matter = contents
+        .strip_prefix("```toml")
+        .ok_or("the file does not start with a ```toml block")?;
+    let (front_matter, markdown) = front_matter
+        .split_once("\n```")
+        .ok_or("the ```toml block is not terminated")?;
+    let front_matter: FrontMatter = toml::from_str(front_matter).map_err(|e| e.to_string())?;
+
+    let metadata = front_matter.advisory;
+    if metadata.withdrawn.is_some() || metadata.informational.is_some() {
+        return Ok(None);
+    }
+
+    let markdown = markdown.trim();
+    let (title, description) = match markdown.strip_prefix("# ") {
+        Some(markdown) => markdown.split_once('\n').unwrap_or((markdown, "")),
+        None => ("", markdown),
+    };
+
+    let parse_reqs = |reqs: Vec<String>| {
+        reqs.iter()
+            .map(|req| VersionReq::parse(req).map_err(|e| format!("invalid version {req}: {e}")))
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    Ok(Some(Advisory {
+        id: metadata.id,
+        package: metadata.package,
+        date: metadata.date,
+        url: metadata.url,
+        aliases: metadata.aliases,
+        cvss: metadata.cvss,
+        references: metadata.references,
+        title: title.trim().to_owned(),
+        description: description.trim().to_owned(),
+        patched: parse_reqs(front_matter.versions.patched)?,
+        unaffected: parse_reqs(front_matter.versions.unaffected)?,
+    }))
+}
+
+/// Computes the base score of a CVSS v3.0 or v3.1 vector, e.g. `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`.
+///
+/// Returns `None` for other versions of CVSS and for malformed vectors.
+/// See <https://www.first.org/cvss/v3.1/specification-document#7-1-Base-Metrics-Equations>
+pub fn cvss3_base_score(vector: &str) -> Option<f32> {
+    let mut metrics = vector.split('/');
+    if !matches!(metrics.next(), Some("CVSS:3.0" | "CVSS:3.1")) {
+        return None;
+    }
+    let metrics: BTreeMap<&str, &str> = metrics.filter_map(|m| m.split_once(':')).collect();
+    let scope_changed = match *metrics.get("S")? {
+        "U" => false,
+        "C" => true,
+        _ => return None,
+    };
+    let attack_vector = match *metrics.get("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        "P" => 0.2,
+        _ => return None,
+    };
+    let attack_complexity = match *metrics.get("AC")? {
+        "L" => 0.77,
+        "H" => 0.44,
+        _ => return None,
+    };
+    let privileges_required = match (*metrics.get("PR")?, scope_changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let user_interaction = match *metrics.get("UI")? {
+        "N" => 0.85,
+        "R" => 0.62,
+        _ => return None,
+    };
+    let impact_metric = |name| match *metrics.get(name)? {
+        "H" => Some(0.56),
+        "L" => Some(0.22),
+        "N" => Some(0.0),
+        _ => None,
+    };
+    let impact_subscore = 1.0
+        - (1.0 - impact_metric("C")?) * (1.0 - impact_metric("I")?) * (1.0 - impact_metric("A")?);
+
+    let impact: f64 = if scope_changed {
+        7.52 * (impact_subscore - 0.029) - 3.25 * f64::powi(impact_subscore - 0.02, 15)
+    } else {
+        6.42 * impact_subscore
+    };
+    let exploitability =
+        8.22 * attack_vector * attack_complexity * privileges_required * user_interaction;
+
+    let score = if impact <= 0.0 {
+        0.0
+    } else if scope_changed {
+        round_up(f64::min(1.08 * (impact + exploitability), 10.0))
+    } else {
+        round_up(f64::min(impact + exploitability, 10.0))
+    };
+    Some(score as f32)
+}
+
+/// Rounds up to one decimal place, avoiding floating point artifacts as defined in CVSS v3.1
+fn round_up(value: f64) -> f64 {
+    let int_input = (value * 100_000.0).round() as u64;
+    if int_input % 10_000 == 0 {
+        int_input as f64 / 100_000.0
+    } else {
+        (int_input / 10_000 + 1) as f64 / 10.0
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum AdvisoryDbError {
+    #[error("Failed to read {}", .path.display())]
+    IoError {
+        path: PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+
+    #[error("Failed to parse the advisory {}: {error}", .path.display())]
+    InvalidAdvisory { path: PathBuf, error: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    const ADVISORY: &str = r#"```toml
+[advisory]
+id = "RUSTSEC-2020-0001"
+package = "vulnerable"
+date = "2020-01-02"
+url = "https://github.com/example/vulnerable/issues/1"
+aliases = ["CVE-2020-1234", "GHSA-abcd-efgh-ijkl"]
+cvss = "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"
+
+[versions]
+patched = [">= 1.2.3"]
+unaffected = ["< 1.0.0"]
+```
+
+# Remote code execution in `vulnerable`
+
+Parsing untrusted input
+executes it.
+"#;
+
+    #[test]
+    fn it_should_match_advisories() {
+        let tmp_dir = assert_fs::TempDir::new().unwrap();
+        tmp_dir
+            .child("crates/vulnerable/RUSTSEC-2020-0001.md")
+            .write_str(ADVISORY)
+            .unwrap();
+        tmp_dir
+            .child("crates/vulnerable/RUSTSEC-2020-0002.md")
+            .write_str(&ADVISORY.replace("[versions]", "withdrawn = \"2020-02-03\"\n[versions]"))
+            .unwrap();
+
+        let database = AdvisoryDatabase::load(tmp_dir.path()).unwrap();
+        let find = |version: &str| {
+            let version = Version::parse(version).unwrap();
+            database
+                .find("vulnerable", &version)
+                .map(|a| a.id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(find("1.2.2"), ["RUSTSEC-2020-0001"]);
+        assert!(find("1.2.3").is_empty());
+        assert!(find("0.9.0").is_empty());
+
+        let version = Version::parse("1.0.0").unwrap();
+        let advisory = database.find("vulnerable", &version).next().unwrap();
+        assert_eq!(advisory.title, "Remote code execution in `vulnerable`");
+        assert_eq!(
+            advisory.description,
+            "Parsing untrusted input\nexecutes it."
+        );
+        assert_eq!(advisory.aliases, ["CVE-2020-1234", "GHSA-abcd-efgh-ijkl"]);
+        assert_eq!(database.find("other", &version).count(), 0);
+
+        tmp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn it_should_compute_cvss3_base_scores() {
+        let score = |vector| cvss3_base_score(vector);
+        assert_eq!(
+            score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"),
+            Some(9.8)
+        );
+        assert_eq!(
+            score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H"),
+            Some(10.0)
+        );
+        assert_eq!(
+            score("CVSS:3.0/AV:L/AC:L/PR:L/UI:N/S:U/C:H/I:N/A:N"),
+            Some(5.5)
+        );
+        assert_eq!(
+            score("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"),
+            Some(6.1)
+        );
+        assert_eq!(
+            score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N"),
+            Some(0.0)
+        );
+        assert_eq!(score("CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N"), None);
+    }
+}
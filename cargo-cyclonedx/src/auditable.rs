@@ -0,0 +1,165 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Reading the dependency list that `cargo auditable` embeds into binaries.
+//!
+//! The list is zlib-compressed JSON stored in a dedicated linker section:
+//! <https://github.com/rust-secure-code/cargo-auditable/blob/master/PARSING.md>
+
+use object::{Object, ObjectSection};
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Name of the section holding the dependency list in ELF, PE and Mach-O files alike.
+/// In Mach-O files it is in the `__DATA` segment.
+const SECTION_NAME: &str = ".dep-v0";
+
+/// Refuse to decompress more than this, in case the section has been tampered with
+const MAX_DECOMPRESSED_SIZE: usize = 8 * 1024 * 1024;
+
+/// The dependency list embedded by `cargo auditable`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct VersionInfo {
+    pub packages: Vec<AuditablePackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AuditablePackage {
+    pub name: String,
+    pub version: String,
+    /// `crates.io`, `git`, `local`, `registry` or the name of another kind of source
+    pub source: String,
+    #[serde(default)]
+    pub kind: DependencyKind,
+    /// Indices of the dependencies in [VersionInfo::packages]
+    #[serde(default)]
+    pub dependencies: Vec<usize>,
+    /// Whether this is the package the binary has been built from
+    #[serde(default)]
+    pub root: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    /// Only used at build time: a build-dependency or a proc-macro
+    Build,
+    #[default]
+    Runtime,
+}
+
+/// Extracts the dependency list from an ELF, Mach-O or PE binary built with `cargo auditable`
+pub fn read_version_info(binary: &[u8]) -> Result<VersionInfo, AuditableError> {
+    let file = object::File::parse(binary).map_err(AuditableError::ObjectParseError)?;
+    let section = file
+        .section_by_name(SECTION_NAME)
+        .ok_or(AuditableError::NoAuditData)?;
+    let compressed = section.data().map_err(AuditableError::ObjectParseError)?;
+
+    let json =
+        miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(compressed, MAX_DECOMPRESSED_SIZE)
+            .map_err(|e| AuditableError::DecompressionError(format!("{:?}", e.status)))?;
+    let version_info: VersionInfo =
+        serde_json::from_slice(&json).map_err(AuditableError::InvalidAuditData)?;
+
+    let package_count = version_info.packages.len();
+    if version_info
+        .packages
+        .iter()
+        .flat_map(|p| &p.dependencies)
+        .any(|&index| index >= package_count)
+    {
+        return Err(AuditableError::InvalidDependencyIndex);
+    }
+    Ok(version_info)
+}
+
+#[derive(Error, Debug)]
+pub enum AuditableError {
+    #[error("Failed to parse the binary")]
+    ObjectParseError(#[source] object::Error),
+
+    #[error("The binary contains no dependency list. Was it built with `cargo auditable`?")]
+    NoAuditData,
+
+    #[error("Failed to decompress the dependency list: {}", .0)]
+    DecompressionError(String),
+
+    #[error("Failed to parse the dependency list")]
+    InvalidAuditData(#[source] serde_json::Error),
+
+    #[error("The dependency list refers to a package that does not exist")]
+    InvalidDependencyIndex,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version_info() {
+        let json = r#"{"packages":[
+            {"name":"app","version":"0.1.0","source":"local","dependencies":[1,2],"root":true},
+            {"name":"itoa","version":"1.0.9","source":"crates.io"},
+            {"name":"cc","version":"1.0.83","source":"crates.io","kind":"build"}
+        ]}"#;
+        let version_info: VersionInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(version_info.packages.len(), 3);
+        assert!(version_info.packages[0].root);
+        assert_eq!(version_info.packages[0].dependencies, vec![1, 2]);
+        assert_eq!(version_info.packages[1].kind, DependencyKind::Runtime);
+        assert_eq!(version_info.packages[2].kind, DependencyKind::Build);
+    }
+
+    #[test]
+    fn read_from_mach_o() {
+        use object::write::Object;
+        use object::{Architecture, BinaryFormat, Endianness, SectionKind};
+
+        let json =
+            r#"{"packages":[{"name":"app","version":"0.1.0","source":"local","root":true}]}"#;
+        let mut object = Object::new(
+            BinaryFormat::MachO,
+            Architecture::Aarch64,
+            Endianness::Little,
+        );
+        let section = object.add_section(
+            b"__DATA".to_vec(),
+            SECTION_NAME.as_bytes().to_vec(),
+            SectionKind::Data,
+        );
+        object.set_section_data(
+            section,
+            miniz_oxide::deflate::compress_to_vec_zlib(json.as_bytes(), 6),
+            1,
+        );
+        let binary = object.write().unwrap();
+
+        let version_info = read_version_info(&binary).unwrap();
+        assert_eq!(version_info.packages.len(), 1);
+        assert_eq!(version_info.packages[0].name, "app");
+    }
+
+    #[test]
+    fn not_a_binary() {
+        assert!(matches!(
+            read_version_info(b"not a binary"),
+            Err(AuditableError::ObjectParseError(_))
+        ));
+    }
+}
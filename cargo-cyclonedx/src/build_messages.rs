@@ -0,0 +1,188 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Reading the `compiler-artifact` messages printed by `cargo build --message-format=json`,
+//! which tell what was actually compiled, with which features and into which files.
+//!
+//! See https://doc.rust-lang.org/cargo/reference/external-tools.html#artifact-messages
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use cargo_metadata::camino::Utf8Path;
+use cargo_metadata::{Artifact, Message};
+use cyclonedx_bom::models::hash::{Hash, HashAlgorithm, HashValue};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Crate types whose output is a final artifact rather than an input to further compilation
+const FINAL_CRATE_TYPES: &[&str] = &["bin", "cdylib", "staticlib"];
+
+/// Debug information and dependency files written next to the artifacts, which are not hashed
+const AUXILIARY_EXTENSIONS: &[&str] = &["d", "pdb", "rlib", "rmeta"];
+
+#[derive(Debug, Default)]
+pub struct BuildArtifacts {
+    artifacts: Vec<Artifact>,
+}
+
+impl BuildArtifacts {
+    /// Reads the messages from a file, or from the standard input if `path` is `-`
+    pub fn load(path: &Path) -> Result<Self, BuildMessagesError> {
+        if path == Path::new("-") {
+            Self::read(std::io::stdin().lock())
+        } else {
+            let file = File::open(path)
+                .map_err(|e| BuildMessagesError::OpenError(path.display().to_string(), e))?;
+            Self::read(BufReader::new(file))
+        }
+    }
+
+    /// Reads the messages, ignoring everything but `compiler-artifact` messages
+    pub fn read(reader: impl BufRead) -> Result<Self, BuildMessagesError> {
+        let mut artifacts = Vec::new();
+        for message in Message::parse_stream(reader) {
+            if let Message::CompilerArtifact(artifact) = message? {
+                artifacts.push(artifact);
+            }
+        }
+        Ok(Self { artifacts })
+    }
+
+    /// Returns `true` if any unit of the package has been compiled
+    pub fn is_compiled(&self, package_id: &str) -> bool {
+        self.artifacts
+            .iter()
+            .any(|artifact| artifact.package_id.repr == package_id)
+    }
+
+    /// Returns the IDs of all packages that have been compiled
+    pub fn compiled_packages(&self) -> BTreeSet<String> {
+        self.artifacts
+            .iter()
+            .map(|artifact| artifact.package_id.repr.clone())
+            .collect()
+    }
+
+    /// Returns the features the package has been compiled with.
+    /// A package compiled both for the host and for the target has the features of both.
+    pub fn features(&self, package_id: &str) -> BTreeSet<String> {
+        self.artifacts
+            .iter()
+            .filter(|artifact| artifact.package_id.repr == package_id)
+            .flat_map(|artifact| artifact.features.iter().cloned())
+            .collect()
+    }
+
+    /// Returns the final artifacts produced for a target, such as the executable of a binary
+    /// or the shared library of a `cdylib`. Libraries only built as `rlib` have none.
+    pub fn output_files(
+        &self,
+        package_id: &str,
+        target_name: &str,
+        target_kind: &[String],
+    ) -> Vec<&Utf8Path> {
+        let mut files = Vec::new();
+        for artifact in self.artifacts.iter().filter(|artifact| {
+            artifact.package_id.repr == package_id
+                && artifact.target.name == target_name
+                && artifact.target.kind == target_kind
+                && artifact
+                    .target
+                    .kind
+                    .iter()
+                    .any(|kind| FINAL_CRATE_TYPES.contains(&kind.as_str()))
+        }) {
+            if let Some(executable) = &artifact.executable {
+                files.push(executable.as_path());
+                continue;
+            }
+            files.extend(
+                artifact
+                    .filenames
+                    .iter()
+                    .filter(|file| {
+                        !file
+                            .extension()
+                            .is_some_and(|extension| AUXILIARY_EXTENSIONS.contains(&extension))
+                    })
+                    .map(|file| file.as_path()),
+            );
+        }
+        files.sort_unstable();
+        files.dedup();
+        files
+    }
+}
+
+/// Computes the SHA-256 hash of a built file
+pub fn artifact_hash(path: &Utf8Path) -> Result<Hash, std::io::Error> {
+    let contents = std::fs::read(path)?;
+    Ok(Hash {
+        alg: HashAlgorithm::SHA_256,
+        content: HashValue(format!("{:x}", Sha256::digest(&contents))),
+    })
+}
+
+#[derive(Error, Debug)]
+pub enum BuildMessagesError {
+    #[error("Failed to open the build messages at {0}")]
+    OpenError(String, #[source] std::io::Error),
+
+    #[error("Failed to read the build messages")]
+    ReadError(#[from] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGES: &str = r#"{"reason":"compiler-artifact","package_id":"registry+https://github.com/rust-lang/crates.io-index#log@0.4.20","manifest_path":"/home/user/.cargo/registry/src/log-0.4.20/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"log","src_path":"/home/user/.cargo/registry/src/log-0.4.20/src/lib.rs","edition":"2015","doc":true,"doctest":true,"test":true},"profile":{"opt_level":"3","debuginfo":0,"debug_assertions":false,"overflow_checks":false,"test":false},"features":["std"],"filenames":["/project/target/release/deps/liblog-1.rlib","/project/target/release/deps/liblog-1.rmeta"],"executable":null,"fresh":true}
+Compiling app v0.1.0 (/project)
+{"reason":"compiler-artifact","package_id":"path+file:///project#app@0.1.0","manifest_path":"/project/Cargo.toml","target":{"kind":["cdylib","rlib"],"crate_types":["cdylib","rlib"],"name":"app","src_path":"/project/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true},"profile":{"opt_level":"3","debuginfo":0,"debug_assertions":false,"overflow_checks":false,"test":false},"features":[],"filenames":["/project/target/release/libapp.so","/project/target/release/libapp.rlib","/project/target/release/deps/libapp-2.rmeta"],"executable":null,"fresh":false}
+{"reason":"compiler-artifact","package_id":"path+file:///project#app@0.1.0","manifest_path":"/project/Cargo.toml","target":{"kind":["bin"],"crate_types":["bin"],"name":"app","src_path":"/project/src/main.rs","edition":"2021","doc":true,"doctest":false,"test":true},"profile":{"opt_level":"3","debuginfo":0,"debug_assertions":false,"overflow_checks":false,"test":false},"features":[],"filenames":["/project/target/release/app"],"executable":"/project/target/release/app","fresh":false}
+{"reason":"build-finished","success":true}
+"#;
+
+    #[test]
+    fn reads_compiler_artifacts() {
+        let artifacts = BuildArtifacts::read(MESSAGES.as_bytes()).unwrap();
+        let log = "registry+https://github.com/rust-lang/crates.io-index#log@0.4.20";
+        let app = "path+file:///project#app@0.1.0";
+
+        assert_eq!(Vec::from_iter(artifacts.compiled_packages()), [app, log]);
+        assert!(!artifacts.is_compiled("path+file:///project#other@0.1.0"));
+        assert_eq!(Vec::from_iter(artifacts.features(log)), ["std"]);
+
+        let kind =
+            |kinds: &[&str]| -> Vec<String> { kinds.iter().map(|k| k.to_string()).collect() };
+        assert_eq!(
+            artifacts.output_files(app, "app", &kind(&["bin"])),
+            [Utf8Path::new("/project/target/release/app")]
+        );
+        assert_eq!(
+            artifacts.output_files(app, "app", &kind(&["cdylib", "rlib"])),
+            [Utf8Path::new("/project/target/release/libapp.so")]
+        );
+        assert!(artifacts
+            .output_files(log, "log", &kind(&["lib"]))
+            .is_empty());
+    }
+}
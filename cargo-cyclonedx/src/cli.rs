@@ -1,14 +1,15 @@
 use cargo_cyclonedx::{
     config::{
-        Describe, Features, FilenameOverride, FilenameOverrideError, FilenamePattern,
-        IncludedDependencies, LicenseParserOptions, OutputOptions, ParseMode, PlatformSuffix,
-        SbomConfig, Target,
+        parse_lifecycle_phase, Describe, Features, FilenameOverride, FilenameOverrideError,
+        FilenamePattern, IncludedDependencies, LicenseParserOptions, OutputOptions, ParseMode,
+        PlatformSuffix, SbomConfig, Target,
     },
     format::Format,
     platform::host_platform,
 };
 use clap::{ArgAction, ArgGroup, Parser};
 use cyclonedx_bom::models::bom::SpecVersion;
+use cyclonedx_bom::models::lifecycle::Phase;
 use std::collections::HashSet;
 use std::iter::FromIterator;
 use std::path;
@@ -63,16 +64,23 @@ pub struct Args {
     /// The target platform to generate the SBOM for, or 'all' for all targets.
     #[clap(
         long = "target",
+        action = ArgAction::Append,
         long_help = "The target to generate the SBOM for, e.g. 'x86_64-unknown-linux-gnu'.
 Use 'all' to include dependencies for all possible targets.
+May be specified multiple times to generate a separate SBOM for each target,
+in which case the target platform is always included in the filename.
 Defaults to the host target, as printed by 'rustc -vV'"
     )]
-    pub target: Option<String>,
+    pub target: Vec<String>,
 
     /// Include the target platform of the BOM in the filename
     #[clap(long = "target-in-filename")]
     pub target_in_filename: bool,
 
+    /// When multiple targets are specified, also emit a SBOM combining all of them
+    #[clap(long = "combine-targets")]
+    pub combine_targets: bool,
+
     /// List all dependencies instead of only top-level ones (default)
     #[clap(long = "all", short = 'a')]
     pub all: bool,
@@ -100,6 +108,111 @@ Defaults to the host target, as printed by 'rustc -vV'"
     /// The CycloneDX specification version to output: `1.3`, `1.4` or `1.5`. Defaults to 1.3
     #[clap(long = "spec-version")]
     pub spec_version: Option<SpecVersion>,
+
+    /// The lifecycle phase described by the SBOM, e.g. `build` or `post-build`. Requires `--spec-version 1.5`
+    #[clap(
+        long = "lifecycle",
+        value_name = "PHASE",
+        value_parser = parse_lifecycle_phase,
+        action = ArgAction::Append
+    )]
+    pub lifecycles: Vec<Phase>,
+
+    /// Emit a single SBOM describing the entire workspace instead of one per member
+    #[clap(long = "workspace-bom", conflicts_with = "describe")]
+    pub workspace_bom: bool,
+
+    /// Include dev-dependencies in the SBOM, marked as excluded from the final artifact
+    #[clap(long = "include-dev-dependencies")]
+    pub include_dev_dependencies: bool,
+
+    /// Omit crates only used at build time: build-dependencies, proc-macros and their dependencies
+    #[clap(long = "exclude-build-dependencies")]
+    pub exclude_build_dependencies: bool,
+
+    /// Generate the SBOM from the dependency list embedded into a binary by `cargo auditable`
+    #[clap(
+        long = "from-binary",
+        value_name = "PATH",
+        value_hint = clap::ValueHint::FilePath,
+        conflicts_with_all = [
+            "manifest_path",
+            "describe",
+            "all_features",
+            "no_default_features",
+            "features",
+            "target",
+            "target_in_filename",
+            "combine_targets",
+            "workspace_bom",
+            "include_dev_dependencies",
+            "exclude_build_dependencies",
+            "profile",
+        ]
+    )]
+    pub from_binary: Option<path::PathBuf>,
+
+    /// Embed the SBOM of every binary into the ELF file of the same name in this directory, e.g. 'target/release'
+    #[clap(
+        long = "embed-in-binaries",
+        value_name = "DIR",
+        value_hint = clap::ValueHint::DirPath,
+        conflicts_with = "from_binary"
+    )]
+    pub embed_in_binaries: Option<path::PathBuf>,
+
+    /// Generate the SBOM from Cargo.lock alone, without invoking cargo. The result is incomplete
+    #[clap(
+        long = "lockfile-only",
+        conflicts_with_all = [
+            "describe",
+            "all_features",
+            "no_default_features",
+            "features",
+            "target",
+            "target_in_filename",
+            "combine_targets",
+            "workspace_bom",
+            "include_dev_dependencies",
+            "exclude_build_dependencies",
+            "from_binary",
+            "embed_in_binaries",
+            "profile",
+        ]
+    )]
+    pub lockfile_only: bool,
+
+    /// Record the advisories from a local clone of the RustSec advisory database as vulnerabilities. Requires `--spec-version 1.4` or later
+    #[clap(
+        long = "advisory-db",
+        value_name = "PATH",
+        value_hint = clap::ValueHint::DirPath
+    )]
+    pub advisory_db: Option<path::PathBuf>,
+
+    /// Read the output of `cargo build --message-format=json` from this file, or from stdin if it is '-',
+    /// to only list the crates that have been compiled and record the hashes of the built binaries
+    #[clap(
+        long = "build-messages",
+        value_name = "FILE",
+        value_hint = clap::ValueHint::FilePath,
+        conflicts_with_all = ["from_binary", "lockfile_only"]
+    )]
+    pub build_messages: Option<path::PathBuf>,
+
+    /// Produce byte-for-byte identical output on every run: take the timestamp from `SOURCE_DATE_EPOCH`,
+    /// derive the serial number from the contents and record local paths relative to the project
+    #[clap(long = "reproducible")]
+    pub reproducible: bool,
+
+    /// List only the crates linked into each target with `--describe binaries`, `--describe all-cargo-targets`
+    /// and `--embed-in-binaries`, using `cargo build --unit-graph`. Requires a nightly toolchain
+    #[clap(long = "unit-graph", conflicts_with_all = ["from_binary", "lockfile_only"])]
+    pub unit_graph: bool,
+
+    /// The Cargo profile the artifacts are built with, recorded along with the toolchain in CycloneDX 1.5 output. The build command is only recorded if it is set
+    #[clap(long = "profile", value_name = "PROFILE-NAME")]
+    pub profile: Option<String>,
 }
 
 impl Args {
@@ -135,14 +248,25 @@ impl Args {
                 })
             };
 
-        let target_string = self.target.clone().unwrap_or_else(host_platform);
-        let target = Some(if &target_string == "all" {
-            Target::AllTargets
-        } else {
-            Target::SingleTarget(target_string)
+        let target = Some(match self.target.as_slice() {
+            [] => Target::SingleTarget(host_platform()),
+            [target] if target == "all" => Target::AllTargets,
+            [target] => Target::SingleTarget(target.clone()),
+            targets => {
+                if targets.iter().any(|t| t == "all") {
+                    return Err(ArgsError::AllTargetsCombined);
+                }
+                // The binaries for each target are built into a different directory
+                if self.embed_in_binaries.is_some() {
+                    return Err(ArgsError::EmbedMultipleTargets);
+                }
+                Target::MultipleTargets(targets.to_vec())
+            }
         });
 
-        let platform_suffix = match self.target_in_filename {
+        // Otherwise the SBOMs for different targets would overwrite each other
+        let multiple_targets = matches!(target, Some(Target::MultipleTargets(_)));
+        let platform_suffix = match self.target_in_filename || multiple_targets {
             true => PlatformSuffix::Included,
             false => PlatformSuffix::NotIncluded,
         };
@@ -160,17 +284,29 @@ impl Args {
             platform_suffix,
         });
 
-        let license_parser = Some(LicenseParserOptions {
-            mode: match self.license_strict {
-                true => ParseMode::Strict,
-                false => ParseMode::Lax,
-            },
-            accept_named: HashSet::from_iter(self.license_accept_named.clone()),
-        });
+        // Leave the license parser options unset if no flags were passed,
+        // so that the settings from `Cargo.toml` apply
+        let license_parser = if !self.license_strict && self.license_accept_named.is_empty() {
+            None
+        } else {
+            Some(LicenseParserOptions {
+                mode: match self.license_strict {
+                    true => ParseMode::Strict,
+                    false => ParseMode::Lax,
+                },
+                accept_named: HashSet::from_iter(self.license_accept_named.clone()),
+            })
+        };
 
         let describe = self.describe;
         let spec_version = self.spec_version;
 
+        let lifecycles = if self.lifecycles.is_empty() {
+            None
+        } else {
+            Some(self.lifecycles.clone())
+        };
+
         Ok(SbomConfig {
             format: self.format,
             included_dependencies,
@@ -180,6 +316,13 @@ impl Args {
             license_parser,
             describe,
             spec_version,
+            lifecycles,
+            workspace_bom: self.workspace_bom.then_some(true),
+            include_dev_dependencies: self.include_dev_dependencies.then_some(true),
+            exclude_build_dependencies: self.exclude_build_dependencies.then_some(true),
+            reproducible: self.reproducible.then_some(true),
+            profile: self.profile.clone(),
+            unit_graph: self.unit_graph.then_some(true),
         })
     }
 }
@@ -188,6 +331,12 @@ impl Args {
 pub enum ArgsError {
     #[error("Invalid filename")]
     FilenameOverrideError(#[from] FilenameOverrideError),
+
+    #[error("'--target all' cannot be combined with other targets")]
+    AllTargetsCombined,
+
+    #[error("'--embed-in-binaries' cannot be used with multiple targets")]
+    EmbedMultipleTargets,
 }
 
 #[cfg(test)]
@@ -222,6 +371,46 @@ mod tests {
         assert!(!contains_feature(&config, ""));
     }
 
+    #[test]
+    fn parse_targets() {
+        let args = vec!["cyclonedx", "--target=all"];
+        let config = parse_to_config(&args);
+        assert_eq!(config.target, Some(Target::AllTargets));
+
+        let args = vec!["cyclonedx", "--target=x86_64-unknown-linux-gnu"];
+        let config = parse_to_config(&args);
+        assert_eq!(
+            config.target,
+            Some(Target::SingleTarget("x86_64-unknown-linux-gnu".to_owned()))
+        );
+        assert_eq!(
+            config.output_options().platform_suffix,
+            PlatformSuffix::NotIncluded
+        );
+
+        let args = vec![
+            "cyclonedx",
+            "--target=x86_64-unknown-linux-gnu",
+            "--target=aarch64-apple-darwin",
+        ];
+        let config = parse_to_config(&args);
+        assert_eq!(
+            config.target,
+            Some(Target::MultipleTargets(vec![
+                "x86_64-unknown-linux-gnu".to_owned(),
+                "aarch64-apple-darwin".to_owned()
+            ]))
+        );
+        assert_eq!(
+            config.output_options().platform_suffix,
+            PlatformSuffix::Included
+        );
+
+        let args = ["cyclonedx", "--target=all", "--target=aarch64-apple-darwin"];
+        let result = Args::parse_from(args.iter()).as_config();
+        assert_eq!(result, Err(ArgsError::AllTargetsCombined));
+    }
+
     fn parse_to_config(args: &[&str]) -> SbomConfig {
         Args::parse_from(args.iter()).as_config().unwrap()
     }
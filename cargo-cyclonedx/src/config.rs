@@ -1,5 +1,6 @@
 use cyclonedx_bom::models::bom::SpecVersion;
-use serde::Deserialize;
+use cyclonedx_bom::models::lifecycle::Phase;
+use serde::{Deserialize, Deserializer};
 use std::collections::HashSet;
 use std::str::FromStr;
 use thiserror::Error;
@@ -33,6 +34,13 @@ pub struct SbomConfig {
     pub license_parser: Option<LicenseParserOptions>,
     pub describe: Option<Describe>,
     pub spec_version: Option<SpecVersion>,
+    pub lifecycles: Option<Vec<Phase>>,
+    pub workspace_bom: Option<bool>,
+    pub include_dev_dependencies: Option<bool>,
+    pub exclude_build_dependencies: Option<bool>,
+    pub reproducible: Option<bool>,
+    pub profile: Option<String>,
+    pub unit_graph: Option<bool>,
 }
 
 impl SbomConfig {
@@ -47,6 +55,7 @@ impl SbomConfig {
             output_options: other
                 .output_options
                 .clone()
+                .map(|other| self.output_options().merge(other))
                 .or_else(|| self.output_options.clone()),
             features: other.features.clone().or_else(|| self.features.clone()),
             target: other.target.clone().or_else(|| self.target.clone()),
@@ -57,6 +66,17 @@ impl SbomConfig {
                 .or_else(|| self.license_parser.clone()),
             describe: other.describe.or(self.describe),
             spec_version: other.spec_version.or(self.spec_version),
+            lifecycles: other.lifecycles.clone().or_else(|| self.lifecycles.clone()),
+            workspace_bom: other.workspace_bom.or(self.workspace_bom),
+            include_dev_dependencies: other
+                .include_dev_dependencies
+                .or(self.include_dev_dependencies),
+            exclude_build_dependencies: other
+                .exclude_build_dependencies
+                .or(self.exclude_build_dependencies),
+            reproducible: other.reproducible.or(self.reproducible),
+            profile: other.profile.clone().or_else(|| self.profile.clone()),
+            unit_graph: other.unit_graph.or(self.unit_graph),
         }
     }
 
@@ -75,12 +95,190 @@ impl SbomConfig {
     pub fn license_parser(&self) -> LicenseParserOptions {
         self.license_parser.clone().unwrap_or_default()
     }
+
+    pub fn spec_version(&self) -> SpecVersion {
+        self.spec_version.unwrap_or_default()
+    }
+
+    /// Whether to emit a single SBOM for the entire workspace instead of one per member
+    pub fn workspace_bom(&self) -> bool {
+        self.workspace_bom.unwrap_or(false)
+    }
+
+    /// Whether to keep dev-dependencies in the SBOM, marked as excluded from the final artifact
+    pub fn include_dev_dependencies(&self) -> bool {
+        self.include_dev_dependencies.unwrap_or(false)
+    }
+
+    /// Whether to omit the crates that are only used at build time,
+    /// i.e. build-dependencies, procedural macros and their dependencies
+    pub fn exclude_build_dependencies(&self) -> bool {
+        self.exclude_build_dependencies.unwrap_or(false)
+    }
+
+    /// Whether the output must only depend on the project and `SOURCE_DATE_EPOCH`,
+    /// not on the time or the directory the SBOM is generated in
+    pub fn reproducible(&self) -> bool {
+        self.reproducible.unwrap_or(false)
+    }
+
+    /// The Cargo profile the artifacts are built with, recorded in the formulation.
+    /// `None` if it has not been configured, since the build itself is not seen.
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Whether to narrow down the dependencies of every target with `cargo build --unit-graph`,
+    /// which is unstable and only available on nightly toolchains
+    pub fn unit_graph(&self) -> bool {
+        self.unit_graph.unwrap_or(false)
+    }
+
+    /// Reads the configuration from the `cyclonedx` key of the `metadata` table
+    /// of a package or workspace, as reported by `cargo metadata`.
+    ///
+    /// `table` is the name of the enclosing table, e.g. `package.metadata`,
+    /// and `manifest_path` is the `Cargo.toml` it was read from; both are only used in error messages.
+    pub fn from_manifest_metadata(
+        metadata: &serde_json::Value,
+        table: &str,
+        manifest_path: &str,
+    ) -> Result<Self, ManifestConfigError> {
+        let table = format!("{table}.cyclonedx");
+        let Some(value) = metadata.get("cyclonedx") else {
+            return Ok(Self::empty_config());
+        };
+
+        let manifest_config: ManifestConfig =
+            serde_path_to_error::deserialize(value).map_err(|err| {
+                ManifestConfigError::InvalidValue {
+                    manifest_path: manifest_path.to_owned(),
+                    table: table.clone(),
+                    key: err.path().to_string(),
+                    message: err.into_inner().to_string(),
+                }
+            })?;
+
+        manifest_config
+            .into_sbom_config()
+            .map_err(|(key, message)| ManifestConfigError::InvalidValue {
+                manifest_path: manifest_path.to_owned(),
+                table,
+                key: key.to_owned(),
+                message,
+            })
+    }
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// The contents of `[package.metadata.cyclonedx]` or `[workspace.metadata.cyclonedx]`
+///
+/// Options that must be known before running `cargo metadata`,
+/// such as the enabled features or the target platform, cannot be set here.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ManifestConfig {
+    format: Option<Format>,
+    describe: Option<Describe>,
+    spec_version: Option<SpecVersion>,
+    included_dependencies: Option<IncludedDependencies>,
+    license_parser: Option<LicenseParserOptions>,
+    output: Option<ManifestOutputOptions>,
+    #[serde(default, deserialize_with = "deserialize_lifecycles")]
+    lifecycles: Option<Vec<Phase>>,
+    /// Only meaningful in `[workspace.metadata.cyclonedx]`
+    workspace_bom: Option<bool>,
+    include_dev_dependencies: Option<bool>,
+    exclude_build_dependencies: Option<bool>,
+    reproducible: Option<bool>,
+    profile: Option<String>,
+    unit_graph: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ManifestOutputOptions {
+    /// Same as `--override-filename` on the command line
+    filename: Option<String>,
+    /// Same as `--target-in-filename` on the command line
+    #[serde(default)]
+    target_in_filename: bool,
+}
+
+impl ManifestConfig {
+    /// Performs the validation that cannot be expressed through `Deserialize`.
+    /// On failure returns the offending key along with the error message.
+    fn into_sbom_config(self) -> Result<SbomConfig, (&'static str, String)> {
+        let output_options = match self.output {
+            Some(output) => {
+                let filename = match output.filename {
+                    Some(filename) => {
+                        let name_override = FilenameOverride::new(filename)
+                            .map_err(|e| ("output.filename", e.to_string()))?;
+                        FilenamePattern::Custom(name_override)
+                    }
+                    None => FilenamePattern::CrateName,
+                };
+                let platform_suffix = match output.target_in_filename {
+                    true => PlatformSuffix::Included,
+                    false => PlatformSuffix::NotIncluded,
+                };
+                Some(OutputOptions {
+                    filename,
+                    platform_suffix,
+                })
+            }
+            None => None,
+        };
+
+        Ok(SbomConfig {
+            format: self.format,
+            included_dependencies: self.included_dependencies,
+            output_options,
+            features: None,
+            target: None,
+            license_parser: self.license_parser,
+            describe: self.describe,
+            spec_version: self.spec_version,
+            lifecycles: self.lifecycles,
+            workspace_bom: self.workspace_bom,
+            include_dev_dependencies: self.include_dev_dependencies,
+            exclude_build_dependencies: self.exclude_build_dependencies,
+            reproducible: self.reproducible,
+            profile: self.profile,
+            unit_graph: self.unit_graph,
+        })
+    }
+}
+
+fn deserialize_lifecycles<'de, D>(deserializer: D) -> Result<Option<Vec<Phase>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let phases: Vec<String> = Vec::deserialize(deserializer)?;
+    phases
+        .iter()
+        .map(|phase| parse_lifecycle_phase(phase).map_err(serde::de::Error::custom))
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ManifestConfigError {
+    #[error("Invalid value for `{key}` in `[{table}]` of {manifest_path}: {message}")]
+    InvalidValue {
+        manifest_path: String,
+        table: String,
+        key: String,
+        message: String,
+    },
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
 pub enum IncludedDependencies {
+    #[serde(rename = "top-level")]
     TopLevelDependencies,
     #[default]
+    #[serde(rename = "all")]
     AllDependencies,
 }
 
@@ -96,12 +294,46 @@ impl FromStr for IncludedDependencies {
     }
 }
 
+/// Parses the name of a CycloneDX 1.5 lifecycle phase, e.g. `build` or `post-build`
+pub fn parse_lifecycle_phase(s: &str) -> Result<Phase, String> {
+    match s {
+        "design" => Ok(Phase::Design),
+        "pre-build" => Ok(Phase::PreBuild),
+        "build" => Ok(Phase::Build),
+        "post-build" => Ok(Phase::PostBuild),
+        "operations" => Ok(Phase::Operations),
+        "discovery" => Ok(Phase::Discovery),
+        "decommission" => Ok(Phase::Decommission),
+        _ => Err(format!(
+            "Expected design, pre-build, build, post-build, operations, discovery or decommission, got `{}`",
+            s
+        )),
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct OutputOptions {
     pub filename: FilenamePattern,
     pub platform_suffix: PlatformSuffix,
 }
 
+impl OutputOptions {
+    /// Neither option can be explicitly reset to its default,
+    /// so the default value is treated as "not set" and does not override anything.
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            filename: match other.filename {
+                FilenamePattern::CrateName => self.filename,
+                custom => custom,
+            },
+            platform_suffix: match other.platform_suffix {
+                PlatformSuffix::NotIncluded => self.platform_suffix,
+                PlatformSuffix::Included => PlatformSuffix::Included,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Features {
     pub all_features: bool,
@@ -114,6 +346,8 @@ pub enum Target {
     #[default]
     AllTargets,
     SingleTarget(String),
+    /// Several targets, each of which is resolved separately and gets its own SBOM
+    MultipleTargets(Vec<String>),
 }
 
 impl Target {
@@ -121,22 +355,19 @@ impl Target {
         match self {
             Target::AllTargets => "all",
             Target::SingleTarget(target) => target.as_str(),
+            // Only used for the SBOM combining all of the targets
+            Target::MultipleTargets(_) => "combined",
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub enum FilenamePattern {
+    #[default]
     CrateName,
     Custom(FilenameOverride),
 }
 
-impl Default for FilenamePattern {
-    fn default() -> Self {
-        Self::CrateName
-    }
-}
-
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum Pattern {
     #[default]
@@ -199,6 +430,7 @@ pub enum PlatformSuffix {
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct LicenseParserOptions {
     /// Use lax or strict parsing
     #[serde(default)]
@@ -232,7 +464,8 @@ pub enum ParseMode {
 }
 
 /// What does the SBOM describe?
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all(deserialize = "kebab-case"))]
 pub enum Describe {
     /// Describe the entire crate in a single SBOM file, with Cargo targets as subcomponents. (default)
     #[default]
@@ -302,6 +535,114 @@ mod test {
         );
     }
 
+    #[test]
+    fn it_should_read_config_from_manifest_metadata() {
+        let metadata = serde_json::json!({
+            "cyclonedx": {
+                "format": "json",
+                "describe": "binaries",
+                "spec-version": "1.5",
+                "included-dependencies": "top-level",
+                "license-parser": { "mode": "strict", "accept-named": ["Foo"] },
+                "output": { "target-in-filename": true },
+                "lifecycles": ["build"],
+            }
+        });
+
+        let config =
+            SbomConfig::from_manifest_metadata(&metadata, "package.metadata", "Cargo.toml")
+                .unwrap();
+
+        assert_eq!(
+            config,
+            SbomConfig {
+                format: Some(Format::Json),
+                included_dependencies: Some(IncludedDependencies::TopLevelDependencies),
+                output_options: Some(OutputOptions {
+                    filename: FilenamePattern::CrateName,
+                    platform_suffix: PlatformSuffix::Included,
+                }),
+                license_parser: Some(LicenseParserOptions {
+                    mode: ParseMode::Strict,
+                    accept_named: ["Foo".into()].into(),
+                }),
+                describe: Some(Describe::Binaries),
+                spec_version: Some(SpecVersion::V1_5),
+                lifecycles: Some(vec![Phase::Build]),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn it_should_ignore_manifest_metadata_without_cyclonedx_key() {
+        let metadata = serde_json::json!({ "docs": { "rs": { "all-features": true } } });
+
+        let config =
+            SbomConfig::from_manifest_metadata(&metadata, "package.metadata", "Cargo.toml")
+                .unwrap();
+
+        assert_eq!(config, SbomConfig::empty_config());
+    }
+
+    #[test]
+    fn it_should_report_the_invalid_key_in_manifest_metadata() {
+        let metadata = serde_json::json!({
+            "cyclonedx": { "license-parser": { "mode": "strictest" } }
+        });
+
+        let actual =
+            SbomConfig::from_manifest_metadata(&metadata, "workspace.metadata", "Cargo.toml")
+                .expect_err("Should not have accepted an unknown license parser mode");
+
+        match actual {
+            ManifestConfigError::InvalidValue { table, key, .. } => {
+                assert_eq!(table, "workspace.metadata.cyclonedx");
+                assert_eq!(key, "license-parser.mode");
+            }
+        }
+
+        let metadata = serde_json::json!({
+            "cyclonedx": { "output": { "filename": format!("a{}b", std::path::MAIN_SEPARATOR) } }
+        });
+
+        let actual =
+            SbomConfig::from_manifest_metadata(&metadata, "package.metadata", "Cargo.toml")
+                .expect_err("Should not have accepted a filename with a path separator");
+
+        match actual {
+            ManifestConfigError::InvalidValue { key, .. } => assert_eq!(key, "output.filename"),
+        }
+    }
+
+    #[test]
+    fn it_should_let_later_configs_override_output_options() {
+        let workspace = SbomConfig {
+            output_options: Some(OutputOptions {
+                filename: FilenamePattern::Custom(FilenameOverride::new("bom").unwrap()),
+                platform_suffix: PlatformSuffix::NotIncluded,
+            }),
+            ..Default::default()
+        };
+        let cli = SbomConfig {
+            output_options: Some(OutputOptions {
+                filename: FilenamePattern::CrateName,
+                platform_suffix: PlatformSuffix::Included,
+            }),
+            ..Default::default()
+        };
+
+        let config = workspace.merge(&cli);
+
+        assert_eq!(
+            config.output_options(),
+            OutputOptions {
+                filename: FilenamePattern::Custom(FilenameOverride::new("bom").unwrap()),
+                platform_suffix: PlatformSuffix::Included,
+            }
+        );
+    }
+
     #[test]
     fn it_should_keep_strict() {
         let config_1 = SbomConfig {
@@ -0,0 +1,271 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Extraction of copyright statements from license and notice files and from the headers of source files.
+
+use cargo_metadata::camino::Utf8Path;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::io::Read;
+
+use crate::license_files::LicenseFile;
+
+/// The extensions of source files whose headers are scanned, including C sources bundled by `-sys` crates
+const SOURCE_EXTENSIONS: &[&str] = &["rs", "c", "h", "cc", "cpp", "hpp", "s"];
+
+/// How much of every source file is considered to be its header
+const HEADER_BYTES: u64 = 8 * 1024;
+const HEADER_LINES: usize = 30;
+
+/// A line starting with a copyright statement, once comment markers have been stripped
+static STATEMENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)^(?:SPDX-FileCopyrightText:\s*(?P<tag>.+)|(?P<statement>(?:copyright\b|\(c\)|©).*))$",
+    )
+    .unwrap()
+});
+
+/// The start of a statement that already says it is a copyright notice
+static COPYRIGHT_PREFIX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^(copyright\b|\(c\)|©)").unwrap());
+
+/// A year, or a copyright sign followed by a holder, which tells statements apart from prose about copyright
+static YEAR_OR_SIGN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(19|20)\d{2}\b|(\(c\)|©)\s*\w").unwrap());
+
+/// Placeholders in license templates, e.g. `Copyright [yyyy] [name of copyright owner]`
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)<(year|yyyy|copyright holders?|owner|name of author|author)>|\[(yyyy|year|name of copyright owner)\]|\byyyy\b|\bYEAR\b")
+        .unwrap()
+});
+
+/// The parts of a statement that are not the name of the holder
+static NOT_HOLDER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)spdx-filecopyrighttext:|copyright|\(c\)|©|all rights reserved|\b(19|20)\d{2}\b|present|[^\w@.]")
+        .unwrap()
+});
+
+/// Finds the copyright statements in the license and notice files of a package and in the headers of its
+/// source files, skipping nested packages and the `target` directory.
+/// Every statement is returned once, in the order it is first found.
+pub fn find_copyright_statements(
+    package_dir: &Utf8Path,
+    license_files: &[LicenseFile],
+) -> Vec<String> {
+    let mut statements = Vec::new();
+    for file in license_files {
+        add_unique(&mut statements, statements_in(&file.text, usize::MAX));
+    }
+    for path in list_source_files(package_dir) {
+        let mut header = Vec::new();
+        let read = std::fs::File::open(&path)
+            .and_then(|file| file.take(HEADER_BYTES).read_to_end(&mut header));
+        if let Err(error) = read {
+            log::debug!("Failed to read {}: {}", path, error);
+            continue;
+        }
+        let header = String::from_utf8_lossy(&header);
+        add_unique(&mut statements, statements_in(&header, HEADER_LINES));
+    }
+    statements
+}
+
+fn add_unique(statements: &mut Vec<String>, found: Vec<String>) {
+    for statement in found {
+        if !statements.contains(&statement) {
+            statements.push(statement);
+        }
+    }
+}
+
+/// Extracts the copyright statements from the first `max_lines` lines of the text
+fn statements_in(text: &str, max_lines: usize) -> Vec<String> {
+    text.lines()
+        .take(max_lines)
+        .filter_map(|line| {
+            let line = strip_comment_markers(line);
+            let captures = STATEMENT.captures(line)?;
+            let statement = match (captures.name("tag"), captures.name("statement")) {
+                // The tag often only holds the year and the holder, e.g. `2021 Jane Doe`
+                (Some(tag), _) if COPYRIGHT_PREFIX.is_match(tag.as_str()) => {
+                    tag.as_str().to_owned()
+                }
+                (Some(tag), _) => format!("Copyright {}", tag.as_str()),
+                (None, Some(statement)) if YEAR_OR_SIGN.is_match(statement.as_str()) => {
+                    statement.as_str().to_owned()
+                }
+                _ => return None,
+            };
+            if PLACEHOLDER.is_match(&statement) {
+                return None;
+            }
+            Some(statement.split_whitespace().collect::<Vec<_>>().join(" "))
+        })
+        .collect()
+}
+
+fn strip_comment_markers(line: &str) -> &str {
+    let mut line = line.trim();
+    for prefix in [
+        "//!", "///", "//", "/*!", "/**", "/*", "<!--", "*", "#", "--", ";",
+    ] {
+        if let Some(rest) = line.strip_prefix(prefix) {
+            line = rest.trim_start();
+            break;
+        }
+    }
+    for suffix in ["*/", "-->"] {
+        if let Some(rest) = line.strip_suffix(suffix) {
+            line = rest.trim_end();
+        }
+    }
+    line
+}
+
+/// Lists the source files in the package directory, sorted by path
+fn list_source_files(package_dir: &Utf8Path) -> Vec<cargo_metadata::camino::Utf8PathBuf> {
+    let mut files = Vec::new();
+    let mut queue = vec![package_dir.to_owned()];
+    while let Some(dir) = queue.pop() {
+        let entries = match dir.read_dir_utf8() {
+            Ok(entries) => entries,
+            Err(error) => {
+                log::debug!("Failed to list {}: {}", dir, error);
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                let name = entry.file_name();
+                let nested_package = path.join("Cargo.toml").is_file();
+                if name != "target" && !name.starts_with('.') && !nested_package {
+                    queue.push(path.to_owned());
+                }
+            } else if path
+                .extension()
+                .is_some_and(|ext| SOURCE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+            {
+                files.push(path.to_owned());
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Summarizes the statements by keeping only the first one for every copyright holder,
+/// so that the same holder is not listed once for every year
+pub fn summarize_copyright(statements: &[String]) -> Option<String> {
+    let mut holders = Vec::new();
+    let mut summary: Vec<&str> = Vec::new();
+    for statement in statements {
+        let holder = NOT_HOLDER
+            .replace_all(statement, " ")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        let holder = if holder.is_empty() {
+            statement.to_lowercase()
+        } else {
+            holder
+        };
+        if !holders.contains(&holder) {
+            holders.push(holder);
+            summary.push(statement);
+        }
+    }
+    if summary.is_empty() {
+        None
+    } else {
+        Some(summary.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+    use cargo_metadata::camino::Utf8PathBuf;
+
+    #[test]
+    fn it_should_extract_statements() {
+        let text = r#"// Copyright (c) 2015-2020 The Example Developers
+// SPDX-FileCopyrightText: 2021 Jane Doe <jane@example.com>
+# SPDX-FileCopyrightText: © 2022 John Doe
+/* Copyright 2019 Someone Else. All rights reserved. */
+//! The copyright notice must be retained.
+//! Copyright [yyyy] [name of copyright owner]
+//! Copyright (C) YEAR by AUTHOR EMAIL
+"#;
+        assert_eq!(
+            statements_in(text, usize::MAX),
+            [
+                "Copyright (c) 2015-2020 The Example Developers",
+                "Copyright 2021 Jane Doe <jane@example.com>",
+                "© 2022 John Doe",
+                "Copyright 2019 Someone Else. All rights reserved.",
+            ]
+        );
+        assert_eq!(statements_in(text, 1).len(), 1);
+    }
+
+    #[test]
+    fn it_should_summarize_statements() {
+        let statements = [
+            "Copyright (c) 2015 The Example Developers".to_owned(),
+            "Copyright 2016-2020 The Example Developers".to_owned(),
+            "© 2021 Jane Doe".to_owned(),
+        ];
+        assert_eq!(
+            summarize_copyright(&statements).unwrap(),
+            "Copyright (c) 2015 The Example Developers; © 2021 Jane Doe"
+        );
+        assert_eq!(summarize_copyright(&[]), None);
+    }
+
+    #[test]
+    fn it_should_scan_source_headers() {
+        let tmp_dir = assert_fs::TempDir::new().unwrap();
+        tmp_dir
+            .child("src/lib.rs")
+            .write_str("// Copyright 2020 Lib Author\npub fn f() {}")
+            .unwrap();
+        tmp_dir
+            .child("target/debug/build/out.rs")
+            .write_str("// Copyright 2020 Generated")
+            .unwrap();
+        tmp_dir.child("nested/Cargo.toml").write_str("").unwrap();
+        tmp_dir
+            .child("nested/src/lib.rs")
+            .write_str("// Copyright 2020 Nested")
+            .unwrap();
+        let package_dir = Utf8PathBuf::try_from(tmp_dir.path().to_path_buf()).unwrap();
+
+        assert_eq!(
+            find_copyright_statements(&package_dir, &[]),
+            ["Copyright 2020 Lib Author"]
+        );
+
+        tmp_dir.close().unwrap();
+    }
+}
@@ -0,0 +1,88 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Embedding SBOMs into ELF binaries.
+//!
+//! The SBOM is stored in a non-allocated section, so it does not affect how the binary runs.
+//! It can be read back with [cyclonedx_bom::embedded::read_from_elf].
+
+use std::path::Path;
+
+use cyclonedx_bom::embedded::ELF_SECTION_NAME;
+use object::build::elf::{Builder, SectionData};
+use thiserror::Error;
+
+/// Stores the JSON-serialized SBOM in the ELF binary at `binary_path`,
+/// replacing any SBOM embedded into it previously
+pub fn embed_into_elf(binary_path: &Path, sbom: &[u8]) -> Result<(), EmbedError> {
+    let io_error = |source| EmbedError::IoError {
+        path: binary_path.display().to_string(),
+        source,
+    };
+    let binary = std::fs::read(binary_path).map_err(io_error)?;
+    let embedded = embed_into_elf_bytes(&binary, sbom).map_err(|e| EmbedError::ElfError {
+        path: binary_path.display().to_string(),
+        message: e.to_string(),
+    })?;
+
+    // Write to a temporary file first to avoid leaving a corrupted binary behind on failure
+    let mut temp_path = binary_path.as_os_str().to_owned();
+    temp_path.push(".cdx-tmp");
+    let temp_path = Path::new(&temp_path);
+    let permissions = std::fs::metadata(binary_path)
+        .map_err(io_error)?
+        .permissions();
+    std::fs::write(temp_path, embedded).map_err(io_error)?;
+    std::fs::set_permissions(temp_path, permissions).map_err(io_error)?;
+    std::fs::rename(temp_path, binary_path).map_err(io_error)?;
+    Ok(())
+}
+
+fn embed_into_elf_bytes(binary: &[u8], sbom: &[u8]) -> Result<Vec<u8>, object::build::Error> {
+    let mut builder = Builder::read(binary)?;
+    for section in builder.sections.iter_mut() {
+        if section.name.as_slice() == ELF_SECTION_NAME.as_bytes() {
+            section.delete = true;
+        }
+    }
+
+    let section = builder.sections.add();
+    section.name = ELF_SECTION_NAME.into();
+    section.sh_type = object::elf::SHT_PROGBITS;
+    // No `SHF_ALLOC` flag, so the section is not loaded into memory
+    section.sh_flags = 0;
+    section.sh_addralign = 1;
+    section.data = SectionData::Data(sbom.into());
+
+    let mut output = Vec::with_capacity(binary.len() + sbom.len());
+    builder.write(&mut output)?;
+    Ok(output)
+}
+
+#[derive(Error, Debug)]
+pub enum EmbedError {
+    #[error("Failed to access {path}")]
+    IoError {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to embed the SBOM into {path}: {message}")]
+    ElfError { path: String, message: String },
+}
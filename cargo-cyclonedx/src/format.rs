@@ -20,19 +20,14 @@ use serde::Deserialize;
 use std::{fmt, str::FromStr};
 
 /// Output format for CycloneDX BOM.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
 #[serde(rename_all(deserialize = "kebab-case"))]
 pub enum Format {
     Json,
+    #[default]
     Xml,
 }
 
-impl Default for Format {
-    fn default() -> Self {
-        Self::Xml
-    }
-}
-
 impl fmt::Display for Format {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
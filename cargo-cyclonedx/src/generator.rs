@@ -1,4 +1,10 @@
+use crate::advisory_db::{cvss3_base_score, Advisory, AdvisoryDatabase};
+use crate::auditable::DependencyKind as AuditableDependencyKind;
+use crate::auditable::{read_version_info, AuditableError, AuditablePackage};
+use crate::build_messages::{artifact_hash, BuildArtifacts};
 use crate::config::Describe;
+use crate::copyright::{find_copyright_statements, summarize_copyright};
+use crate::embed::{embed_into_elf, EmbedError};
 /*
  * This file is part of CycloneDX Rust Cargo.
  *
@@ -18,10 +24,26 @@ use crate::config::Describe;
  */
 use crate::config::FilenamePattern;
 use crate::config::PlatformSuffix;
-use crate::config::SbomConfig;
+use crate::config::Target;
 use crate::config::{IncludedDependencies, ParseMode};
+use crate::config::{ManifestConfigError, SbomConfig};
 use crate::format::Format;
-use crate::purl::get_purl;
+use crate::git_source::GitSource;
+use crate::license_files::{
+    check_declared_license, find_license_files, read_license_file, LicenseFile, LicenseFileKind,
+};
+use crate::lockfile::{locate_cargo_lock, read_lockfile};
+use crate::native::{is_source_crate, native_library, NativeLibrary};
+use crate::overrides::{find_overrides, OriginalSource, Override, OverrideKind};
+use crate::platform::cargo_home;
+use crate::purl::get_crates_io_purl;
+use crate::purl::get_lockfile_purl;
+use crate::purl::get_purl_relative_to;
+use crate::reproducible::{self, ReproducibleError};
+use crate::source_hash::package_source_hash;
+use crate::toolchain::{create_formula, merge_formula, read_profile_settings, Toolchain};
+use crate::unit_graph::UnitGraph;
+use crate::vendor::{verify_vendored_source, VendorError, VendoredSource};
 
 use cargo_metadata;
 use cargo_metadata::DependencyKind;
@@ -33,29 +55,57 @@ use cargo_metadata::PackageId;
 
 use cargo_lock::package::Checksum;
 use cargo_lock::Lockfile;
-use cargo_metadata::camino::Utf8PathBuf;
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
+use cargo_metadata::semver::Version;
+use cyclonedx_bom::external_models::date_time::DateTime;
 use cyclonedx_bom::external_models::normalized_string::NormalizedString;
-use cyclonedx_bom::external_models::spdx::SpdxExpression;
+use cyclonedx_bom::external_models::spdx::{SpdxExpression, SpdxIdentifier};
 use cyclonedx_bom::external_models::uri::Uri;
+use cyclonedx_bom::models::advisory::{Advisories, Advisory as CdxAdvisory};
 use cyclonedx_bom::models::attached_text::AttachedText;
-use cyclonedx_bom::models::bom::Bom;
-use cyclonedx_bom::models::component::{Classification, Component, Components, Scope};
+use cyclonedx_bom::models::bom::BomReference;
+use cyclonedx_bom::models::bom::{Bom, SpecVersion};
+use cyclonedx_bom::models::component::{
+    Classification, Component, ComponentEvidence, Components, ConfidenceScore, Copyright,
+    CopyrightTexts, Identity, IdentityField, Method, Methods, Occurrence, Occurrences, Pedigree,
+    Scope,
+};
+use cyclonedx_bom::models::composition::{AggregateType, Composition, Compositions};
 use cyclonedx_bom::models::dependency::{Dependencies, Dependency};
 use cyclonedx_bom::models::external_reference::{
     ExternalReference, ExternalReferenceType, ExternalReferences,
 };
+use cyclonedx_bom::models::formulation::Formula;
 use cyclonedx_bom::models::license::{License, LicenseChoice, Licenses};
+use cyclonedx_bom::models::lifecycle::{Lifecycle, Lifecycles};
 use cyclonedx_bom::models::metadata::Metadata;
 use cyclonedx_bom::models::metadata::MetadataError;
 use cyclonedx_bom::models::organization::OrganizationalContact;
+use cyclonedx_bom::models::property::{Properties, Property};
+use cyclonedx_bom::models::service::Services;
 use cyclonedx_bom::models::tool::{Tool, Tools};
+use cyclonedx_bom::models::vulnerability::{Vulnerabilities, Vulnerability};
+use cyclonedx_bom::models::vulnerability_rating::{
+    Score, ScoreMethod, Severity, VulnerabilityRating, VulnerabilityRatings,
+};
+use cyclonedx_bom::models::vulnerability_reference::{
+    VulnerabilityReference, VulnerabilityReferences,
+};
+use cyclonedx_bom::models::vulnerability_source::VulnerabilitySource;
+use cyclonedx_bom::models::vulnerability_target::{
+    Version as TargetVersion, Versions, VulnerabilityTarget, VulnerabilityTargets,
+};
+use cyclonedx_bom::prelude::Purl as CdxPurl;
 use cyclonedx_bom::validation::Validate;
 use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
 use regex::Regex;
 
 use log::Level;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::fs::File;
 use std::io::BufWriter;
@@ -68,11 +118,84 @@ use validator::validate_email;
 // Maps from PackageId to Package for efficiency - faster lookups than in a Vec
 type PackageMap = BTreeMap<PackageId, Package>;
 type ResolveMap = BTreeMap<PackageId, Node>;
+type DependencyInfoMap = HashMap<PackageId, DependencyInfo>;
 
 pub struct SbomGenerator {
     config: SbomConfig,
     workspace_root: Utf8PathBuf,
+    target_directory: Utf8PathBuf,
     crate_hashes: HashMap<cargo_metadata::PackageId, Checksum>,
+    vendored_sources: HashMap<PackageId, VendoredSource>,
+    overrides: HashMap<PackageId, Override>,
+    dependency_info: DependencyInfoMap,
+    /// The features enabled for each package
+    active_features: HashMap<PackageId, Vec<String>>,
+}
+
+/// How a dependency is used by the package described in the SBOM.
+/// Ordered from the strongest to the weakest relationship.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum DependencyUsage {
+    /// Compiled into the final artifact
+    Normal,
+    /// Only used at build time, by build scripts or procedural macros
+    Build,
+    /// Only used by tests, examples and benchmarks
+    Dev,
+}
+
+impl DependencyUsage {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DependencyUsage::Normal => "normal",
+            DependencyUsage::Build => "build",
+            DependencyUsage::Dev => "dev",
+        }
+    }
+
+    fn scope(&self) -> Scope {
+        match self {
+            DependencyUsage::Normal => Scope::Required,
+            DependencyUsage::Build | DependencyUsage::Dev => Scope::Excluded,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DependencyInfo {
+    usage: DependencyUsage,
+    /// Every crate depending on this one declares it as `optional`, i.e. it is enabled by a feature
+    optional: bool,
+    /// The features of dependent crates that enabled this optional dependency,
+    /// in the `crate/feature` form
+    enabled_by: BTreeSet<String>,
+    /// The `cfg()` predicates or target triples under which dependent crates use this one.
+    /// Empty if at least one crate depends on it unconditionally.
+    conditions: BTreeSet<String>,
+    /// The conditional dependency relationships on this crate,
+    /// as pairs of the `bom-ref` of the dependent crate and the condition
+    edge_conditions: BTreeSet<(String, String)>,
+}
+
+impl DependencyInfo {
+    /// Combines the information gathered for different workspace members
+    fn merge(&self, other: &DependencyInfo) -> DependencyInfo {
+        DependencyInfo {
+            usage: self.usage.min(other.usage),
+            optional: self.optional && other.optional,
+            enabled_by: self.enabled_by.union(&other.enabled_by).cloned().collect(),
+            conditions: if self.conditions.is_empty() || other.conditions.is_empty() {
+                BTreeSet::new()
+            } else {
+                self.conditions.union(&other.conditions).cloned().collect()
+            },
+            edge_conditions: self
+                .edge_conditions
+                .union(&other.edge_conditions)
+                .cloned()
+                .collect(),
+        }
+    }
 }
 
 /// Contains a map from `bom_ref` of a subcomponent to the kinds of Cargo targets it has,
@@ -90,45 +213,74 @@ impl SbomGenerator {
         config: &SbomConfig,
     ) -> Result<Vec<GeneratedSbom>, GeneratorError> {
         log::trace!("Processing the workspace {}", meta.workspace_root);
+        let workspace_manifest = meta.workspace_root.join("Cargo.toml");
+        let workspace_config = SbomConfig::from_manifest_metadata(
+            &meta.workspace_metadata,
+            "workspace.metadata",
+            workspace_manifest.as_str(),
+        )?;
+
+        if workspace_config.merge(config).workspace_bom() {
+            let sbom = Self::create_workspace_sbom(meta, &workspace_config.merge(config))?;
+            return Ok(vec![sbom]);
+        }
+
         let members: Vec<PackageId> = meta.workspace_members;
         let packages = index_packages(meta.packages);
         let resolve = index_resolve(meta.resolve.unwrap().nodes);
 
+        // All workspace members share the same `Cargo.lock`
+        let lockfile = load_lockfile(workspace_manifest.as_std_path());
+        let crate_hashes = lockfile.as_ref().map(package_hashes).unwrap_or_default();
+        let vendored_sources = verify_vendored_sources(&packages, &resolve, &crate_hashes)?;
+        let overrides = find_overrides(
+            &meta.workspace_root,
+            lockfile.as_ref(),
+            resolve.keys().map(|id| &packages[id]),
+        );
+
+        let toolchain = OnceCell::new();
         let mut result = Vec::with_capacity(members.len());
         for member in members.iter() {
             log::trace!("Processing the package {}", member);
 
-            let (dependencies, pruned_resolve) =
-                if config.included_dependencies() == IncludedDependencies::AllDependencies {
-                    all_dependencies(member, &packages, &resolve)
-                } else {
-                    top_level_dependencies(member, &packages, &resolve)
-                };
-
-            let manifest_path = packages[member].manifest_path.clone().into_std_path_buf();
+            // Settings from the command line take precedence over the package manifest,
+            // which in turn takes precedence over the workspace manifest
+            let package_config = SbomConfig::from_manifest_metadata(
+                &packages[member].metadata,
+                "package.metadata",
+                packages[member].manifest_path.as_str(),
+            )?;
+            let config = &workspace_config.merge(&package_config).merge(config);
+            if config.describe.unwrap_or_default() != Describe::Crate
+                && config.output_options().filename != FilenamePattern::CrateName
+            {
+                return Err(GeneratorError::FilenameOverrideConflict {
+                    package_name: packages[member].name.clone(),
+                });
+            }
 
-            let mut crate_hashes = HashMap::new();
-            match locate_cargo_lock(&manifest_path) {
-                Ok(path) => match Lockfile::load(path) {
-                    Ok(lockfile_contents) => crate_hashes = package_hashes(&lockfile_contents),
-                    Err(err) => log::warn!(
-                        "Failed to parse `Cargo.lock`: {err}\n\
-                        Hashes will not be included in the SBOM."
-                    ),
-                },
-                Err(err) => log::warn!(
-                    "Failed to locate `Cargo.lock`: {err}\n\
-                    Hashes will not be included in the SBOM."
-                ),
+            let (mut dependencies, mut pruned_resolve, dependency_info) =
+                prune_dependencies(member, &packages, &resolve, config);
+            if config.exclude_build_dependencies() {
+                remove_build_dependencies(&mut dependencies, &mut pruned_resolve, &dependency_info);
             }
 
+            let manifest_path = packages[member].manifest_path.clone().into_std_path_buf();
+
             let generator = SbomGenerator {
                 config: config.clone(),
                 workspace_root: meta.workspace_root.to_owned(),
-                crate_hashes,
+                target_directory: meta.target_directory.to_owned(),
+                crate_hashes: crate_hashes.clone(),
+                vendored_sources: vendored_sources.clone(),
+                overrides: overrides.clone(),
+                dependency_info,
+                active_features: active_features(&pruned_resolve),
             };
-            let (bom, target_kinds) =
+            let (mut bom, target_kinds) =
                 generator.create_bom(member, &dependencies, &pruned_resolve)?;
+            bom.formulation = create_formulation(&toolchain, &meta.workspace_root, config);
 
             let generated = GeneratedSbom {
                 bom,
@@ -144,6 +296,339 @@ impl SbomGenerator {
         Ok(result)
     }
 
+    /// Creates a single SBOM describing the entire workspace.
+    ///
+    /// The root package, or the workspace itself if it is virtual, is the toplevel component,
+    /// with every other workspace member as its subcomponent.
+    /// The dependencies of all members are merged, so every package is listed only once.
+    ///
+    /// Only the configuration from the workspace manifest and the command line applies,
+    /// since there is no single package to read it from.
+    fn create_workspace_sbom(
+        meta: CargoMetadata,
+        config: &SbomConfig,
+    ) -> Result<GeneratedSbom, GeneratorError> {
+        if config.describe.unwrap_or_default() != Describe::Crate {
+            return Err(GeneratorError::WorkspaceBomDescribeConflict);
+        }
+
+        let resolve = meta.resolve.unwrap();
+        let root = resolve.root;
+        let members: Vec<PackageId> = meta.workspace_members;
+        let packages = index_packages(meta.packages);
+        let resolve = index_resolve(resolve.nodes);
+
+        let manifest_path = meta.workspace_root.join("Cargo.toml").into_std_path_buf();
+        let lockfile = load_lockfile(&manifest_path);
+        let crate_hashes = lockfile.as_ref().map(package_hashes).unwrap_or_default();
+        let vendored_sources = verify_vendored_sources(&packages, &resolve, &crate_hashes)?;
+        let overrides = find_overrides(
+            &meta.workspace_root,
+            lockfile.as_ref(),
+            resolve.keys().map(|id| &packages[id]),
+        );
+
+        let mut workspace_packages = PackageMap::new();
+        let mut workspace_resolve = ResolveMap::new();
+        let mut dependency_info = DependencyInfoMap::new();
+        for member in members.iter() {
+            log::trace!("Processing the package {}", member);
+
+            let (dependencies, pruned_resolve, member_info) =
+                prune_dependencies(member, &packages, &resolve, config);
+
+            workspace_packages.extend(dependencies);
+            for node in pruned_resolve.into_values() {
+                merge_node(&mut workspace_resolve, node);
+            }
+            for (id, info) in member_info {
+                let merged = match dependency_info.get(&id) {
+                    Some(existing) => info.merge(existing),
+                    None => info,
+                };
+                dependency_info.insert(id, merged);
+            }
+        }
+        // Workspace members are described in the metadata regardless of how they are used
+        dependency_info.retain(|id, _| !members.contains(id));
+        if config.exclude_build_dependencies() {
+            remove_build_dependencies(
+                &mut workspace_packages,
+                &mut workspace_resolve,
+                &dependency_info,
+            );
+        }
+
+        let package_name = match &root {
+            Some(root) => packages[root].name.clone(),
+            None => meta
+                .workspace_root
+                .file_name()
+                .unwrap_or("workspace")
+                .to_owned(),
+        };
+
+        let generator = SbomGenerator {
+            config: config.clone(),
+            workspace_root: meta.workspace_root.to_owned(),
+            target_directory: meta.target_directory.to_owned(),
+            crate_hashes,
+            vendored_sources,
+            overrides,
+            dependency_info,
+            active_features: active_features(&workspace_resolve),
+        };
+        let (mut bom, target_kinds) = generator.create_workspace_bom(
+            root.as_ref(),
+            &members,
+            &workspace_packages,
+            &workspace_resolve,
+        )?;
+        bom.formulation = create_formulation(&OnceCell::new(), &meta.workspace_root, config);
+
+        Ok(GeneratedSbom {
+            bom,
+            manifest_path,
+            package_name,
+            sbom_config: generator.config,
+            target_kinds,
+        })
+    }
+
+    /// Creates a SBOM for a binary built with `cargo auditable` from the dependency list embedded in it.
+    /// The source code of the binary is not needed.
+    ///
+    /// Only the name, version and kind of source of each package are recorded in the binary,
+    /// so components from sources other than crates.io have no PURL.
+    pub fn create_sbom_from_binary(
+        binary_path: &Path,
+        config: &SbomConfig,
+    ) -> Result<GeneratedSbom, GeneratorError> {
+        let binary =
+            std::fs::read(binary_path).map_err(|error| GeneratorError::BinaryReadError {
+                path: binary_path.to_owned(),
+                error,
+            })?;
+        let packages = read_version_info(&binary)?.packages;
+        let binary_name = binary_path
+            .file_stem()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let root = packages.iter().position(|p| p.root);
+        let included: Vec<usize> = match (root, config.included_dependencies()) {
+            (Some(root), IncludedDependencies::TopLevelDependencies) => {
+                packages[root].dependencies.clone()
+            }
+            _ => (0..packages.len()).filter(|&i| Some(i) != root).collect(),
+        };
+        let bom_ref = |index: usize| auditable_bom_ref(&packages[index]);
+
+        let mut metadata = Self::create_empty_metadata(config)?;
+        let root_ref = match root {
+            Some(root) => {
+                let mut component = create_auditable_component(
+                    &packages[root],
+                    bom_ref(root),
+                    config.spec_version(),
+                );
+                component.component_type = Classification::Application;
+                metadata.component = Some(component);
+                bom_ref(root)
+            }
+            None => {
+                log::warn!(
+                    "The dependency list in {} has no root package",
+                    binary_path.display()
+                );
+                let component = Component::new(Classification::Application, &binary_name, "", None);
+                metadata.component = Some(component);
+                binary_name.clone()
+            }
+        };
+
+        let components = included
+            .iter()
+            .map(|&index| {
+                create_auditable_component(&packages[index], bom_ref(index), config.spec_version())
+            })
+            .collect();
+
+        let mut dependencies = Vec::new();
+        if let Some(root) = root {
+            dependencies.push(Dependency {
+                dependency_ref: root_ref,
+                dependencies: packages[root]
+                    .dependencies
+                    .iter()
+                    .map(|&dep| bom_ref(dep))
+                    .collect(),
+            });
+        }
+        for &index in &included {
+            dependencies.push(Dependency {
+                dependency_ref: bom_ref(index),
+                dependencies: packages[index]
+                    .dependencies
+                    .iter()
+                    .filter(|dep| included.contains(dep))
+                    .map(|&dep| bom_ref(dep))
+                    .collect(),
+            });
+        }
+
+        let bom = Bom {
+            metadata: Some(metadata),
+            components: Some(Components(components)),
+            dependencies: Some(Dependencies(dependencies)),
+            ..Bom::default()
+        };
+
+        Ok(GeneratedSbom {
+            bom,
+            manifest_path: binary_path.to_owned(),
+            package_name: binary_name,
+            sbom_config: config.clone(),
+            target_kinds: TargetKinds(HashMap::new()),
+        })
+    }
+
+    /// Creates a SBOM from `Cargo.lock` alone, without invoking Cargo.
+    ///
+    /// `Cargo.lock` lists every package that may be used on any platform with any features,
+    /// without telling dependency kinds apart, and records no licenses or locations of local packages.
+    /// The composition of the SBOM is therefore marked as incomplete.
+    pub fn create_sbom_from_lockfile(
+        manifest_path: &Path,
+        config: &SbomConfig,
+    ) -> Result<GeneratedSbom, GeneratorError> {
+        let lockfile_error = |error: String| GeneratorError::LockfileError {
+            manifest_path: manifest_path.to_owned(),
+            error,
+        };
+        let lockfile_path =
+            locate_cargo_lock(manifest_path).map_err(|e| lockfile_error(e.to_string()))?;
+        let lockfile = read_lockfile(&lockfile_path).map_err(|e| lockfile_error(e.to_string()))?;
+        let packages = &lockfile.packages;
+
+        // Only the manifest tells which of the local packages is the root
+        let root_name = read_package_name(manifest_path);
+        let root = root_name.as_deref().and_then(|name| {
+            packages
+                .iter()
+                .position(|p| p.source.is_none() && p.name.as_str() == name)
+        });
+        let dependencies: Vec<Vec<usize>> = packages
+            .iter()
+            .map(|package| {
+                package
+                    .dependencies
+                    .iter()
+                    .filter_map(|dep| {
+                        packages.iter().position(|candidate| {
+                            dep.matches(candidate)
+                                && dep.source.as_ref().map_or(true, |source| {
+                                    candidate.source.as_ref() == Some(source)
+                                })
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let workspace_dir = lockfile_path.parent().unwrap();
+        let workspace_dir =
+            Utf8Path::from_path(workspace_dir).unwrap_or(Utf8Path::new("workspace"));
+        let (root_ref, root_dependencies, mut root_component) = match root {
+            Some(root) => {
+                let package = &packages[root];
+                let mut component = create_lockfile_component(package, config.spec_version());
+                component.component_type = Classification::Application;
+                (pkgid(package), dependencies[root].clone(), component)
+            }
+            None => {
+                // A virtual workspace, whose members are all the local packages
+                let members = (0..packages.len())
+                    .filter(|&i| packages[i].source.is_none())
+                    .collect();
+                let name = root_name
+                    .as_deref()
+                    .or(workspace_dir.file_name())
+                    .unwrap_or("workspace");
+                let component = Component::new(
+                    Classification::Application,
+                    name,
+                    "",
+                    Some(virtual_workspace_ref(workspace_dir)),
+                );
+                (virtual_workspace_ref(workspace_dir), members, component)
+            }
+        };
+        // The version is only optional since CycloneDX 1.4
+        if root.is_none() && config.spec_version() >= SpecVersion::V1_4 {
+            root_component.version = None;
+        }
+
+        let included: Vec<usize> = match config.included_dependencies() {
+            IncludedDependencies::TopLevelDependencies => root_dependencies.clone(),
+            IncludedDependencies::AllDependencies => {
+                (0..packages.len()).filter(|&i| Some(i) != root).collect()
+            }
+        };
+
+        let mut metadata = Self::create_empty_metadata(config)?;
+        let package_name = root_component.name.to_string();
+        metadata.component = Some(root_component);
+
+        let components = included
+            .iter()
+            .map(|&index| create_lockfile_component(&packages[index], config.spec_version()))
+            .collect();
+
+        let mut bom_dependencies = vec![Dependency {
+            dependency_ref: root_ref.clone(),
+            dependencies: root_dependencies
+                .iter()
+                .map(|&dep| pkgid(&packages[dep]))
+                .collect(),
+        }];
+        for &index in &included {
+            bom_dependencies.push(Dependency {
+                dependency_ref: pkgid(&packages[index]),
+                dependencies: dependencies[index]
+                    .iter()
+                    .filter(|dep| included.contains(dep))
+                    .map(|&dep| pkgid(&packages[dep]))
+                    .collect(),
+            });
+        }
+
+        let composition = Composition {
+            bom_ref: None,
+            aggregate: AggregateType::Incomplete,
+            assemblies: None,
+            dependencies: Some(vec![BomReference::new(root_ref)]),
+            vulnerabilities: None,
+            signature: None,
+        };
+
+        let bom = Bom {
+            metadata: Some(metadata),
+            components: Some(Components(components)),
+            dependencies: Some(Dependencies(bom_dependencies)),
+            compositions: Some(Compositions(vec![composition])),
+            ..Bom::default()
+        };
+
+        Ok(GeneratedSbom {
+            bom,
+            manifest_path: manifest_path.to_owned(),
+            package_name,
+            sbom_config: config.clone(),
+            target_kinds: TargetKinds(HashMap::new()),
+        })
+    }
+
     fn create_bom(
         &self,
         package: &PackageId,
@@ -151,12 +636,12 @@ impl SbomGenerator {
         resolve: &ResolveMap,
     ) -> Result<(Bom, TargetKinds), GeneratorError> {
         let mut bom = Bom::default();
-        let root_package = &packages[package];
+        let root_dir = package_dir(&packages[package]);
 
         let components: Vec<_> = packages
             .values()
             .filter(|p| &p.id != package)
-            .map(|component| self.create_component(component, root_package))
+            .map(|component| self.create_component(component, root_dir))
             .collect();
 
         bom.components = Some(Components(components));
@@ -166,15 +651,158 @@ impl SbomGenerator {
         bom.metadata = Some(metadata);
 
         bom.dependencies = Some(create_dependencies(resolve));
+        self.add_native_libraries(&mut bom, packages, resolve);
+
+        Ok((bom, target_kinds))
+    }
+
+    fn create_workspace_bom(
+        &self,
+        root: Option<&PackageId>,
+        members: &[PackageId],
+        packages: &PackageMap,
+        resolve: &ResolveMap,
+    ) -> Result<(Bom, TargetKinds), GeneratorError> {
+        let mut bom = Bom::default();
+
+        let components: Vec<_> = packages
+            .values()
+            .filter(|p| !members.contains(&p.id))
+            .map(|component| self.create_component(component, &self.workspace_root))
+            .collect();
+
+        bom.components = Some(Components(components));
+
+        let root = root.map(|id| &packages[id]);
+        let members: Vec<&Package> = members
+            .iter()
+            .filter(|id| Some(*id) != root.map(|r| &r.id))
+            .map(|id| &packages[id])
+            .collect();
+        let (metadata, target_kinds) = self.create_workspace_metadata(root, &members)?;
+
+        let mut dependencies = create_dependencies(resolve);
+        if root.is_none() {
+            // The virtual workspace does not appear in `cargo metadata`, so record its members manually
+            dependencies.0.push(Dependency {
+                dependency_ref: virtual_workspace_ref(&self.workspace_root),
+                dependencies: members.iter().map(|p| p.id.to_string()).collect(),
+            });
+        }
+
+        bom.metadata = Some(metadata);
+        bom.dependencies = Some(dependencies);
+        self.add_native_libraries(&mut bom, packages, resolve);
 
         Ok((bom, target_kinds))
     }
 
-    fn create_component(&self, package: &Package, root_package: &Package) -> Component {
+    /// Describes the native libraries that packages declare with `links` as components,
+    /// which the crate linking each library depends on.
+    /// A library built from a `-src` crate in turn depends on that crate.
+    fn add_native_libraries(&self, bom: &mut Bom, packages: &PackageMap, resolve: &ResolveMap) {
+        let components = bom.components.get_or_insert(Components(Vec::new()));
+        let dependencies = bom.dependencies.get_or_insert(Dependencies(Vec::new()));
+        for node in resolve.values() {
+            let Some(package) = packages.get(&node.id) else {
+                continue;
+            };
+            let source_crate = node
+                .dependencies
+                .iter()
+                .filter_map(|id| packages.get(id))
+                .find(|dependency| is_source_crate(dependency));
+            let Some(library) = native_library(package, source_crate) else {
+                continue;
+            };
+
+            let crate_ref = package.id.to_string();
+            let library_ref = format!("{} links:{}", crate_ref, library.name);
+            let crate_component = components
+                .0
+                .iter()
+                .find(|c| c.bom_ref.as_ref() == Some(&crate_ref));
+            // Workspace members are not listed among the components and are always required
+            let scope = crate_component
+                .and_then(|c| c.scope.clone())
+                .unwrap_or(Scope::Required);
+            // The `-src` crate is not listed if only top-level dependencies are included
+            let source_ref =
+                source_crate
+                    .map(|source| source.id.to_string())
+                    .filter(|source_ref| {
+                        components
+                            .0
+                            .iter()
+                            .any(|c| c.bom_ref.as_ref() == Some(source_ref))
+                    });
+
+            components.0.push(self.create_native_component(
+                package,
+                &library,
+                library_ref.clone(),
+                scope,
+            ));
+            if let Some(dependency) = dependencies
+                .0
+                .iter_mut()
+                .find(|d| d.dependency_ref == crate_ref)
+            {
+                dependency.dependencies.push(library_ref.clone());
+            }
+            dependencies.0.push(Dependency {
+                dependency_ref: library_ref,
+                dependencies: source_ref.into_iter().collect(),
+            });
+        }
+    }
+
+    fn create_native_component(
+        &self,
+        package: &Package,
+        library: &NativeLibrary,
+        bom_ref: String,
+        scope: Scope,
+    ) -> Component {
+        let version = library.version.as_deref().unwrap_or_default();
+        let mut component = Component::new(
+            Classification::Library,
+            &library.name,
+            version,
+            Some(bom_ref),
+        );
+        // The version is required before CycloneDX 1.4, so it is left empty instead
+        if library.version.is_none() && self.config.spec_version() >= SpecVersion::V1_4 {
+            component.version = None;
+        }
+        component.scope = Some(scope);
+        component.description = Some(NormalizedString::new(&format!(
+            "Native library linked by the {} crate",
+            package.name
+        )));
+        if !library.bundled_sources.is_empty() {
+            let properties = library
+                .bundled_sources
+                .iter()
+                .map(|dir| Property::new("cdx:cargo:bundled-source", dir))
+                .collect();
+            component.properties = Some(Properties(properties));
+        }
+        component
+    }
+
+    /// `root_dir` is the directory relative to which the paths of local packages are recorded
+    fn create_component(&self, package: &Package, root_dir: &Utf8Path) -> Component {
         let name = package.name.to_owned().trim().to_string();
         let version = package.version.to_string();
 
-        let purl = match get_purl(package, root_package, &self.workspace_root, None) {
+        let purl = match get_purl_relative_to(
+            package,
+            root_dir,
+            &self.workspace_root,
+            None,
+            self.config.reproducible(),
+        ) {
             Ok(purl) => Some(purl),
             Err(e) => {
                 log::warn!("Package {} has an invalid Purl: {} ", package.name, e);
@@ -191,9 +819,80 @@ impl SbomGenerator {
 
         component.purl = purl;
         component.scope = Some(Scope::Required);
+        component.properties = self.get_cargo_properties(package);
+        if let Some(info) = self.dependency_info.get(&package.id) {
+            component.scope = Some(info.usage.scope());
+        }
         component.external_references = Self::get_external_references(package);
-        component.licenses = self.get_licenses(package);
+        component.pedigree = match self.overrides.get(&package.id) {
+            Some(package_override) => {
+                component.modified = Some(true);
+                Some(self.get_override_pedigree(package, package_override, component.purl.clone()))
+            }
+            None => Self::get_pedigree(package),
+        };
+        let license_files = Self::get_license_files(package);
+        component.licenses = self.get_licenses(package, &license_files);
+        if !license_files.is_empty() {
+            let properties = component.properties.get_or_insert(Properties(Vec::new()));
+            for file in &license_files {
+                let name = match file.kind {
+                    LicenseFileKind::License => "cdx:cargo:license-file",
+                    LicenseFileKind::Notice => "cdx:cargo:notice-file",
+                };
+                properties.0.push(Property::new(name, &file.relative_path));
+            }
+        }
+        // A `license-file` on its own declares the license as well
+        let mismatches = match (&package.license, &package.license_file) {
+            (None, Some(_)) => Vec::new(),
+            (license, _) => check_declared_license(license.as_deref(), &license_files),
+        };
+        for mismatch in mismatches {
+            log::warn!(
+                "The license of package {} does not match its license files: {}",
+                package.name,
+                mismatch
+            );
+            let properties = component.properties.get_or_insert(Properties(Vec::new()));
+            properties
+                .0
+                .push(Property::new("cdx:cargo:license-mismatch", &mismatch));
+        }
         component.hashes = self.get_hashes(package);
+        if let Some(vendored) = self.vendored_sources.get(&package.id) {
+            if self.config.spec_version() >= SpecVersion::V1_5 {
+                component.evidence = Some(self.create_vendored_evidence(vendored));
+            }
+        }
+        // The text of a `license-file` declared on its own is already attached to the license
+        let attached_file = match &package.license {
+            Some(_) => None,
+            None => package.license_file(),
+        };
+        if let Some(licenses) = create_license_evidence(&license_files, attached_file.as_deref()) {
+            evidence_mut(&mut component).licenses = Some(licenses);
+        }
+        if self.config.spec_version() >= SpecVersion::V1_5 {
+            let has_purl = component.purl.is_some();
+            let evidence = evidence_mut(&mut component);
+            if evidence.occurrences.is_none() {
+                let location = self.source_location(package);
+                evidence.occurrences = Some(Occurrences(vec![Occurrence::new(&location)]));
+            }
+            if evidence.identity.is_none() && has_purl {
+                evidence.identity = Some(purl_identity(
+                    manifest_analysis("cargo metadata", MANIFEST_CONFIDENCE),
+                    self.crate_hashes.get(&package.id),
+                ));
+            }
+        }
+        let copyrights = find_copyright_statements(package_dir(package), &license_files);
+        component.copyright = summarize_copyright(&copyrights).map(|s| NormalizedString::new(&s));
+        if !copyrights.is_empty() {
+            let texts = copyrights.into_iter().map(Copyright).collect();
+            evidence_mut(&mut component).copyright = Some(CopyrightTexts(texts));
+        }
 
         component.description = package
             .description
@@ -205,8 +904,12 @@ impl SbomGenerator {
 
     /// Same as [Self::create_component] but also includes information
     /// on binaries and libraries comprising it as subcomponents
-    fn create_toplevel_component(&self, package: &Package) -> (Component, TargetKinds) {
-        let mut top_component = self.create_component(package, package);
+    fn create_toplevel_component(
+        &self,
+        package: &Package,
+        root_dir: &Utf8Path,
+    ) -> (Component, TargetKinds) {
+        let mut top_component = self.create_component(package, root_dir);
         let mut subcomponents: Vec<Component> = Vec::new();
         let mut target_kinds = HashMap::new();
         for tgt in filter_targets(&package.targets) {
@@ -255,13 +958,15 @@ impl SbomGenerator {
             // When using a git repo that contains a workspace, Cargo will automatically select
             // the right package out of the workspace. Paths can then be resolved relatively to it.
             // So the information we encode here is sufficient to idenfity the file in git too.
-            let package_dir = package
-                .manifest_path
-                .parent()
-                .expect("manifest_path in `cargo metadata` output is not a file!");
-            if let Ok(relative_path) = tgt.src_path.strip_prefix(package_dir) {
-                subcomponent.purl =
-                    get_purl(package, package, &self.workspace_root, Some(relative_path)).ok();
+            if let Ok(relative_path) = tgt.src_path.strip_prefix(package_dir(package)) {
+                subcomponent.purl = get_purl_relative_to(
+                    package,
+                    root_dir,
+                    &self.workspace_root,
+                    Some(relative_path),
+                    self.config.reproducible(),
+                )
+                .ok();
             } else {
                 log::warn!(
                     "Source path \"{}\" is not a subpath of workspace root \"{}\"",
@@ -276,6 +981,160 @@ impl SbomGenerator {
         (top_component, TargetKinds(target_kinds))
     }
 
+    /// Records how the package is used and configured, which is not expressible through the scope alone
+    fn get_cargo_properties(&self, package: &Package) -> Option<Properties> {
+        let mut properties = Vec::new();
+        if let Some(info) = self.dependency_info.get(&package.id) {
+            properties.push(Property::new(
+                "cdx:cargo:dependency-kind",
+                info.usage.as_str(),
+            ));
+            if info.optional {
+                properties.push(Property::new("cdx:cargo:optional", "true"));
+            }
+            for feature in &info.enabled_by {
+                properties.push(Property::new("cdx:cargo:enabled-by", feature));
+            }
+            for condition in &info.conditions {
+                properties.push(Property::new("cdx:cargo:cfg", condition));
+            }
+            for (dependent, condition) in &info.edge_conditions {
+                let value = format!("{} {}", dependent, condition);
+                properties.push(Property::new("cdx:cargo:edge-cfg", &value));
+            }
+        }
+        if is_proc_macro(package) {
+            properties.push(Property::new("cdx:cargo:proc-macro", "true"));
+        }
+        for feature in self.active_features.get(&package.id).into_iter().flatten() {
+            properties.push(Property::new("cdx:cargo:feature", feature));
+        }
+        properties.extend(Self::get_manifest_properties(package));
+        if let Some(git) = GitSource::from_package(package) {
+            if let Some(reference) = &git.reference {
+                properties.push(Property::new("cdx:cargo:git-ref", reference));
+            }
+            if let Some(commit) = &git.commit {
+                properties.push(Property::new("cdx:cargo:git-commit", commit));
+            }
+        }
+
+        if properties.is_empty() {
+            None
+        } else {
+            Some(Properties(properties))
+        }
+    }
+
+    /// Returns the directory the sources of the package are read from, relative to Cargo's
+    /// home directory for the registry cache and git checkouts, or to the workspace root otherwise
+    fn source_location(&self, package: &Package) -> String {
+        let directory = package_dir(package);
+        if let Ok(relative) = directory.strip_prefix(&self.workspace_root) {
+            return match relative.as_str() {
+                "" => ".".to_owned(),
+                relative => relative.to_owned(),
+            };
+        }
+        if let Some(relative) = cargo_home().and_then(|home| {
+            directory
+                .as_std_path()
+                .strip_prefix(home)
+                .ok()
+                .map(Path::to_owned)
+        }) {
+            return format!(
+                "$CARGO_HOME/{}",
+                relative.to_string_lossy().replace('\\', "/")
+            );
+        }
+        match pathdiff::diff_paths(directory, &self.workspace_root) {
+            Some(relative) => relative.to_string_lossy().replace('\\', "/"),
+            None => directory.to_string(),
+        }
+    }
+
+    /// Records the metadata from the `[package]` section of `Cargo.toml` that has no counterpart in CycloneDX
+    fn get_manifest_properties(package: &Package) -> Vec<Property> {
+        let mut properties = vec![Property::new("cdx:rust:edition", package.edition.as_str())];
+        if let Some(rust_version) = &package.rust_version {
+            properties.push(Property::new(
+                "cdx:rust:rust-version",
+                &rust_version.to_string(),
+            ));
+        }
+        for category in &package.categories {
+            properties.push(Property::new("cdx:cargo:category", category));
+        }
+        for keyword in &package.keywords {
+            properties.push(Property::new("cdx:cargo:keyword", keyword));
+        }
+        if let Some(readme) = &package.readme {
+            let readme = readme.strip_prefix(package_dir(package)).unwrap_or(readme);
+            properties.push(Property::new("cdx:cargo:readme", readme.as_str()));
+        }
+        match &package.publish {
+            None => (),
+            Some(registries) if registries.is_empty() => {
+                properties.push(Property::new("cdx:cargo:publish", "false"));
+            }
+            Some(registries) => {
+                for registry in registries {
+                    properties.push(Property::new("cdx:cargo:publish", registry));
+                }
+            }
+        }
+        if let Some(default_run) = &package.default_run {
+            properties.push(Property::new("cdx:cargo:default-run", default_run));
+        }
+        if let Some(registry) = package.source.as_ref().and_then(registry_name) {
+            properties.push(Property::new("cdx:cargo:registry", registry));
+        }
+        properties
+    }
+
+    /// Records the files of a vendored crate, which have been verified against
+    /// `.cargo-checksum.json`, as evidence of the component identity
+    fn create_vendored_evidence(&self, vendored: &VendoredSource) -> ComponentEvidence {
+        let directory = vendored
+            .directory
+            .strip_prefix(&self.workspace_root)
+            .unwrap_or(&vendored.directory);
+
+        let mut occurrences = Vec::new();
+        let mut methods = Vec::new();
+        if let Some(checksum) = &vendored.package_checksum {
+            methods.push(Method {
+                technique: "hash-comparison".to_owned(),
+                confidence: ConfidenceScore::new(1.0),
+                value: Some(checksum.clone()),
+            });
+        }
+        for (file, hash) in &vendored.files {
+            let location = format!("{directory}/{file}");
+            methods.push(Method {
+                technique: "hash-comparison".to_owned(),
+                confidence: ConfidenceScore::new(1.0),
+                // Same format as the output of `sha256sum`
+                value: Some(format!("{hash}  {location}")),
+            });
+            occurrences.push(Occurrence::new(&location));
+        }
+
+        ComponentEvidence {
+            licenses: None,
+            copyright: None,
+            occurrences: Some(Occurrences(occurrences)),
+            callstack: None,
+            identity: Some(Identity {
+                field: IdentityField::Hash,
+                confidence: Some(ConfidenceScore::new(1.0)),
+                methods: Some(Methods(methods)),
+                tools: None,
+            }),
+        }
+    }
+
     fn get_classification(pkg: &Package) -> Classification {
         // Transitive dependencies that contain both libraries and binaries
         // get surfaces only as a library by `cargo metadata`.
@@ -324,27 +1183,31 @@ impl SbomGenerator {
             }
         }
 
-        if let Some(other) = &package.links {
-            match Uri::try_from(other.to_string()) {
-                Ok(uri) => {
-                    references.push(ExternalReference::new(ExternalReferenceType::Other, uri))
-                }
+        if let Some(vcs) = &package.repository {
+            match Uri::try_from(vcs.to_string()) {
+                Ok(uri) => references.push(ExternalReference::new(ExternalReferenceType::Vcs, uri)),
                 Err(e) => log::warn!(
-                    "Package {} has an invalid links URI ({}): {} ",
+                    "Package {} has an invalid repository URI ({}): {} ",
                     package.name,
-                    other,
+                    vcs,
                     e
                 ),
             }
         }
 
-        if let Some(vcs) = &package.repository {
-            match Uri::try_from(vcs.to_string()) {
-                Ok(uri) => references.push(ExternalReference::new(ExternalReferenceType::Vcs, uri)),
+        if let Some(git) = GitSource::from_package(package) {
+            let revision_url = git.revision_url();
+            match Uri::try_from(revision_url.clone()) {
+                Ok(uri) => {
+                    let mut reference = ExternalReference::new(ExternalReferenceType::Vcs, uri);
+                    reference.comment =
+                        Some("The revision the package was fetched from".to_owned());
+                    references.push(reference)
+                }
                 Err(e) => log::warn!(
-                    "Package {} has an invalid repository URI ({}): {} ",
+                    "Package {} has an invalid git source URI ({}): {} ",
                     package.name,
-                    vcs,
+                    revision_url,
                     e
                 ),
             }
@@ -357,9 +1220,181 @@ impl SbomGenerator {
         None
     }
 
-    fn get_licenses(&self, package: &Package) -> Option<Licenses> {
-        let mut licenses = vec![];
+    /// Records the crates.io crate that a package fetched from git was forked from
+    fn get_pedigree(package: &Package) -> Option<Pedigree> {
+        let git = GitSource::from_package(package)?;
+        if !git.is_fork_of_published_crate(package) {
+            return None;
+        }
+
+        let version = package.version.to_string();
+        let mut ancestor = Component::new(Classification::Library, &package.name, &version, None);
+        ancestor.purl = match get_crates_io_purl(&package.name, Some(&version)) {
+            Ok(purl) => Some(purl),
+            Err(e) => {
+                log::warn!("Package {} has an invalid Purl: {} ", package.name, e);
+                None
+            }
+        };
+
+        Some(Pedigree {
+            ancestors: Some(Components(vec![ancestor])),
+            descendants: None,
+            variants: None,
+            commits: None,
+            patches: None,
+            notes: Some(format!(
+                "Fetched from {} instead of the upstream repository {}",
+                git.repository,
+                package.repository.as_deref().unwrap_or_default()
+            )),
+        })
+    }
+
+    /// Records the package that would have been used if it had not been overridden as the ancestor,
+    /// and the package it has been overridden with as the variant.
+    ///
+    /// `[patch]` does not record which version would have been used without the patch,
+    /// and the patch may well have a version that has never been published.
+    /// The ancestor then has no version and a PURL without version,
+    /// and records the requirement of its dependents, e.g. `^1`, as `cdx:cargo:version-requirement`.
+    fn get_override_pedigree(
+        &self,
+        package: &Package,
+        package_override: &Override,
+        purl: Option<CdxPurl>,
+    ) -> Pedigree {
+        let version = package_override.original_version.as_deref();
+        let mut ancestor = Component::new(
+            Classification::Library,
+            &package.name,
+            version.unwrap_or_default(),
+            None,
+        );
+        if version.is_none() {
+            // The version is required before CycloneDX 1.4, so it is left empty instead
+            if self.config.spec_version() >= SpecVersion::V1_4 {
+                ancestor.version = None;
+            }
+            if let Some(requirement) = &package_override.original_requirement {
+                ancestor.properties = Some(Properties(vec![Property::new(
+                    "cdx:cargo:version-requirement",
+                    requirement,
+                )]));
+            }
+        }
+        match &package_override.original_source {
+            OriginalSource::CratesIo => match get_crates_io_purl(&package.name, version) {
+                Ok(purl) => ancestor.purl = Some(purl),
+                Err(e) => log::warn!("Package {} has an invalid Purl: {} ", package.name, e),
+            },
+            OriginalSource::Url(url) => match Uri::try_from(url.clone()) {
+                Ok(uri) => {
+                    ancestor.external_references =
+                        Some(ExternalReferences(vec![ExternalReference::new(
+                            ExternalReferenceType::Distribution,
+                            uri,
+                        )]))
+                }
+                Err(e) => log::warn!(
+                    "Package {} has an invalid source URI ({}): {}",
+                    package.name,
+                    url,
+                    e
+                ),
+            },
+            // The URL of a named registry is only known to Cargo
+            OriginalSource::Registry(_) => {}
+        }
+
+        let mut variant = Component::new(
+            Classification::Library,
+            &package.name,
+            &package.version.to_string(),
+            None,
+        );
+        variant.purl = purl;
+
+        let notes = match package_override.kind {
+            OverrideKind::Patch => format!(
+                "Overridden with [patch.{}] in Cargo.toml",
+                package_override.original_source.as_str()
+            ),
+            OverrideKind::Replace => "Overridden with [replace] in Cargo.toml".to_owned(),
+        };
+
+        Pedigree {
+            ancestors: Some(Components(vec![ancestor])),
+            descendants: None,
+            variants: Some(Components(vec![variant])),
+            commits: None,
+            patches: None,
+            notes: Some(notes),
+        }
+    }
+
+    /// Finds the license and notice files in the package sources.
+    /// Failures are not fatal: the SBOM is simply emitted without them.
+    fn get_license_files(package: &Package) -> Vec<LicenseFile> {
+        let package_dir = package_dir(package);
+        let mut files = Vec::new();
 
+        // It is possible to specify both a named license and a license file in Cargo.toml.
+        // If that happens, we encode both.
+        let declared_file = package.license_file();
+        if let Some(license_file) = &declared_file {
+            let relative_path = match license_file.strip_prefix(package_dir) {
+                Ok(path) => path
+                    .components()
+                    .map(|c| c.as_str())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                Err(_) => license_file.to_string(),
+            };
+            match read_license_file(
+                license_file.clone(),
+                relative_path,
+                LicenseFileKind::License,
+            ) {
+                Ok(Some(file)) => files.push(file),
+                Ok(None) => log::warn!(
+                    "License file '{}' for package {} is not valid UTF-8",
+                    license_file,
+                    package.name
+                ),
+                Err(error) => log::warn!(
+                    "Failed to read license file '{}' for package {}: {}",
+                    license_file,
+                    package.name,
+                    error
+                ),
+            }
+        }
+
+        // Many crates ship license and notice files without declaring them in `license-file`
+        match find_license_files(package_dir) {
+            Ok(found) => {
+                let declared_file = declared_file.and_then(|path| path.canonicalize_utf8().ok());
+                files.extend(found.into_iter().filter(|file| {
+                    declared_file.is_none() || declared_file != file.path.canonicalize_utf8().ok()
+                }));
+            }
+            Err(error) => {
+                log::warn!(
+                    "Failed to look for license files of package {} in {}: {}",
+                    package.name,
+                    package_dir,
+                    error
+                );
+            }
+        }
+        files
+    }
+
+    /// Returns the license declared in `Cargo.toml`: the `license` expression if there is one,
+    /// otherwise the `license-file`. Undeclared license files are only recorded as evidence,
+    /// since CycloneDX does not allow mixing an expression with individual licenses.
+    fn get_licenses(&self, package: &Package, license_files: &[LicenseFile]) -> Option<Licenses> {
         if let Some(license) = &package.license {
             let parse_mode = self
                 .config
@@ -380,8 +1415,8 @@ impl SbomGenerator {
                 ParseMode::Lax => SpdxExpression::parse_lax(license.to_string()),
             };
 
-            match result {
-                Ok(expression) => licenses.push(LicenseChoice::Expression(expression)),
+            let choice = match result {
+                Ok(expression) => LicenseChoice::Expression(expression),
                 Err(err) => {
                     let level = match &self.config.license_parser {
                         Some(opts) if opts.accept_named.contains(license) => Level::Info,
@@ -394,53 +1429,55 @@ impl SbomGenerator {
                         license,
                         err,
                     );
-                    licenses.push(LicenseChoice::License(License::named_license(license)))
-                }
-            }
-        }
-
-        // Check for license file.
-        // It is possible to specify both a named license and a license file in Cargo.toml.
-        // If that happens, we encode both.
-        if let Some(license_file) = package.license_file().as_ref() {
-            match std::fs::read_to_string(license_file.as_path()) {
-                Ok(content) => {
-                    let mut license = License::named_license("Unknown");
-                    let encoded_text = AttachedText::new(None, content);
-                    license.text = Some(encoded_text);
-                    licenses.push(LicenseChoice::License(license));
-                }
-                Err(error) => {
-                    log::warn!(
-                        "Failed to read license file '{}' for package {}: {}",
-                        package.name,
-                        license_file,
-                        error
-                    );
+                    LicenseChoice::License(License::named_license(license))
                 }
-            }
+            };
+            return Some(Licenses(vec![choice]));
         }
 
-        if licenses.is_empty() {
-            log::trace!(
-                "Package {} has no licenses or license file specified",
-                package.name
-            );
+        // A `license-file` on its own declares the license as well
+        let declared_file = package.license_file()?;
+        let Some(file) = license_files.iter().find(|file| file.path == declared_file) else {
+            log::trace!("Package {} has no readable license file", package.name);
             return None;
-        }
-
-        Some(Licenses(licenses))
+        };
+        let mut license = match &file.license {
+            Some(id) => file_license(id),
+            None => License::named_license("Unknown"),
+        };
+        license.text = Some(AttachedText::new(None, file.text.clone()));
+        Some(Licenses(vec![LicenseChoice::License(license)]))
     }
 
     fn get_hashes(&self, package: &Package) -> Option<cyclonedx_bom::models::hash::Hashes> {
         match self.crate_hashes.get(&package.id) {
             Some(hash) => Some(cyclonedx_bom::models::hash::Hashes(vec![to_bom_hash(hash)])),
+            None if is_local_or_git(package) => {
+                // Local and git packages have no checksum in Cargo.lock, so hash their sources instead
+                let filename_override = match self.config.output_options().filename {
+                    FilenamePattern::CrateName => None,
+                    FilenamePattern::Custom(name_override) => Some(name_override.to_string()),
+                };
+                match package_source_hash(
+                    &package.manifest_path,
+                    &self.target_directory,
+                    filename_override.as_deref(),
+                ) {
+                    Ok(hash) => Some(cyclonedx_bom::models::hash::Hashes(vec![hash])),
+                    Err(err) => {
+                        log::warn!(
+                            "Failed to hash the sources of package {}: {}",
+                            package.name,
+                            anyhow::Error::from(err)
+                        );
+                        None
+                    }
+                }
+            }
             None => {
                 // Log level is set to debug because this is perfectly normal:
-                // First, only Rust 1.77 and later has `cargo metadata` output pkgid format,
+                // only Rust 1.77 and later has `cargo metadata` output pkgid format,
                 // so anything prior to that won't match.
-                // Second, only packages coming from registries have a checksum associated with them,
-                // while local or git packages do not have a checksum and that too is normal.
                 log::debug!(
                     "Hash for package ID {} not found in Cargo.lock",
                     &package.id
@@ -456,24 +1493,135 @@ impl SbomGenerator {
     ) -> Result<(Metadata, TargetKinds), GeneratorError> {
         let authors = Self::create_authors(package);
 
-        let mut metadata = Metadata::new()?;
+        let mut metadata = Self::create_empty_metadata(&self.config)?;
         if !authors.is_empty() {
             metadata.authors = Some(authors);
         }
 
-        let (mut component, target_kinds) = self.create_toplevel_component(package);
+        let (mut component, target_kinds) =
+            self.create_toplevel_component(package, package_dir(package));
 
         component.component_type = Self::get_classification(package);
 
         metadata.component = Some(component);
 
-        let tool = Tool::new("CycloneDX", "cargo-cyclonedx", env!("CARGO_PKG_VERSION"));
+        Ok((metadata, target_kinds))
+    }
+
+    /// Same as [Self::create_metadata], but with the workspace members as subcomponents
+    /// of the toplevel component. `members` must not include the `root` package.
+    fn create_workspace_metadata(
+        &self,
+        root: Option<&Package>,
+        members: &[&Package],
+    ) -> Result<(Metadata, TargetKinds), GeneratorError> {
+        let mut metadata = Self::create_empty_metadata(&self.config)?;
+
+        let (mut component, mut target_kinds) = match root {
+            Some(root) => {
+                let authors = Self::create_authors(root);
+                if !authors.is_empty() {
+                    metadata.authors = Some(authors);
+                }
+
+                let (mut component, target_kinds) =
+                    self.create_toplevel_component(root, &self.workspace_root);
+                component.component_type = Self::get_classification(root);
+                (component, target_kinds)
+            }
+            None => (
+                self.create_virtual_workspace_component(),
+                TargetKinds(HashMap::new()),
+            ),
+        };
+
+        let mut subcomponents = component.components.take().unwrap_or(Components(vec![]));
+        for member in members {
+            let (mut member_component, member_target_kinds) =
+                self.create_toplevel_component(member, &self.workspace_root);
+            member_component.component_type = Self::get_classification(member);
+            target_kinds.0.extend(member_target_kinds.0);
+            subcomponents.0.push(member_component);
+        }
+        component.components = Some(subcomponents);
 
-        metadata.tools = Some(Tools::List(vec![tool]));
+        metadata.component = Some(component);
 
         Ok((metadata, target_kinds))
     }
 
+    /// Describes a virtual workspace, i.e. one without a root package
+    fn create_virtual_workspace_component(&self) -> Component {
+        let name = self.workspace_root.file_name().unwrap_or("workspace");
+        let mut component = Component::new(
+            Classification::Application,
+            name,
+            "",
+            Some(virtual_workspace_ref(&self.workspace_root)),
+        );
+        // A virtual workspace has no version of its own,
+        // but the version field is only optional since CycloneDX 1.4
+        if self.config.spec_version() >= SpecVersion::V1_4 {
+            component.version = None;
+        }
+        component
+    }
+
+    /// Creates the metadata with the fields that do not depend on the package being described
+    fn create_empty_metadata(config: &SbomConfig) -> Result<Metadata, GeneratorError> {
+        let mut metadata = Metadata::new()?;
+
+        if config.spec_version() >= SpecVersion::V1_5 {
+            metadata.tools = Some(Tools::Object {
+                services: Services(vec![]),
+                components: Components(vec![Self::create_tool_component()]),
+            });
+
+            if let Some(phases) = &config.lifecycles {
+                let lifecycles = phases.iter().cloned().map(Lifecycle::Phase).collect();
+                metadata.lifecycles = Some(Lifecycles(lifecycles));
+            }
+        } else {
+            let tool = Tool::new("CycloneDX", "cargo-cyclonedx", env!("CARGO_PKG_VERSION"));
+            metadata.tools = Some(Tools::List(vec![tool]));
+
+            if config.lifecycles.is_some() {
+                log::warn!(
+                    "Lifecycles are only supported in CycloneDX 1.5 and later, omitting them from the SBOM"
+                );
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    /// Describes cargo-cyclonedx itself as a component,
+    /// as required by the `tools` field starting with CycloneDX 1.5
+    fn create_tool_component() -> Component {
+        let version = env!("CARGO_PKG_VERSION");
+        let mut component = Component::new(
+            Classification::Application,
+            env!("CARGO_PKG_NAME"),
+            version,
+            None,
+        );
+
+        component.author = Some(NormalizedString::new("CycloneDX"));
+        component.description = Some(NormalizedString::new(env!("CARGO_PKG_DESCRIPTION")));
+        component.purl = CdxPurl::new("cargo", env!("CARGO_PKG_NAME"), version).ok();
+
+        let mut references = Vec::new();
+        if let Ok(uri) = Uri::try_from(env!("CARGO_PKG_HOMEPAGE").to_string()) {
+            references.push(ExternalReference::new(ExternalReferenceType::Website, uri));
+        }
+        if let Ok(uri) = Uri::try_from(env!("CARGO_PKG_REPOSITORY").to_string()) {
+            references.push(ExternalReference::new(ExternalReferenceType::Vcs, uri));
+        }
+        component.external_references = Some(ExternalReferences(references));
+
+        component
+    }
+
     fn create_authors(package: &Package) -> Vec<OrganizationalContact> {
         let mut authors = vec![];
         let mut invalid_authors = vec![];
@@ -528,6 +1676,33 @@ fn filter_targets(
     })
 }
 
+/// Returns the name Cargo uses for crates.io, or the index URL for other registries,
+/// as the names of other registries are only known to the Cargo configuration
+fn registry_name(source: &cargo_metadata::Source) -> Option<&str> {
+    if source.is_crates_io() || source.repr == "sparse+https://index.crates.io/" {
+        Some("crates-io")
+    } else if let Some(index) = source.repr.strip_prefix("registry+") {
+        Some(index)
+    } else if source.repr.starts_with("sparse+") {
+        Some(&source.repr)
+    } else {
+        None
+    }
+}
+
+/// Returns the directory containing the `Cargo.toml` of the package
+fn package_dir(package: &Package) -> &Utf8Path {
+    package
+        .manifest_path
+        .parent()
+        .expect("manifest_path in `cargo metadata` output is not a file!")
+}
+
+/// Returns the `bom-ref` of a virtual workspace, formatted like the IDs of local packages
+fn virtual_workspace_ref(workspace_root: &Utf8Path) -> String {
+    format!("path+file://{}", workspace_root)
+}
+
 fn index_packages(packages: Vec<Package>) -> PackageMap {
     packages
         .into_iter()
@@ -561,34 +1736,388 @@ pub enum GeneratorError {
         error: anyhow::Error,
     },
 
-    #[error("Error creating Metadata")]
-    MetadataError(#[from] MetadataError),
+    #[error("Error creating Metadata")]
+    MetadataError(#[from] MetadataError),
+
+    #[error("Could not parse author string: {}", .0)]
+    AuthorParseError(String),
+
+    #[error("Invalid SBOM configuration in Cargo.toml")]
+    ManifestConfigError(#[from] ManifestConfigError),
+
+    #[error("The output filename of package {package_name} is overridden, but `describe` is set to emit multiple SBOM files")]
+    FilenameOverrideConflict { package_name: String },
+
+    #[error("`describe` cannot be used together with a workspace-wide SBOM")]
+    WorkspaceBomDescribeConflict,
+
+    #[error("Failed to verify the vendored sources")]
+    VendorError(#[from] VendorError),
+
+    #[error("Failed to read the binary {path}")]
+    BinaryReadError {
+        path: PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+
+    #[error("Failed to read the dependency list embedded in the binary")]
+    AuditableError(#[from] AuditableError),
+
+    #[error("Failed to read the Cargo.lock for {manifest_path}: {error}")]
+    LockfileError {
+        manifest_path: PathBuf,
+        error: String,
+    },
+}
+
+fn create_lockfile_component(
+    package: &cargo_lock::Package,
+    spec_version: SpecVersion,
+) -> Component {
+    let mut component = Component::new(
+        Classification::Library,
+        package.name.as_str(),
+        &package.version.to_string(),
+        Some(pkgid(package)),
+    );
+    component.purl = match get_lockfile_purl(package) {
+        Some(Ok(purl)) => Some(purl),
+        Some(Err(e)) => {
+            log::warn!("Package {} has an invalid Purl: {} ", package.name, e);
+            None
+        }
+        None => None,
+    };
+    component.hashes = package
+        .checksum
+        .as_ref()
+        .map(|checksum| cyclonedx_bom::models::hash::Hashes(vec![to_bom_hash(checksum)]));
+    if spec_version >= SpecVersion::V1_5 && component.purl.is_some() {
+        evidence_mut(&mut component).identity = Some(purl_identity(
+            manifest_analysis("Cargo.lock", LOCKFILE_CONFIDENCE),
+            package.checksum.as_ref(),
+        ));
+    }
+    component
+}
+
+/// Reads the package name from `Cargo.toml`, returning `None` for a virtual workspace
+fn read_package_name(manifest_path: &Path) -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct Manifest {
+        package: Option<ManifestPackage>,
+    }
+    #[derive(serde::Deserialize)]
+    struct ManifestPackage {
+        name: String,
+    }
+
+    let contents = match std::fs::read_to_string(manifest_path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            log::warn!("Failed to read {}: {}", manifest_path.display(), e);
+            return None;
+        }
+    };
+    match toml::from_str::<Manifest>(&contents) {
+        Ok(manifest) => manifest.package.map(|package| package.name),
+        Err(e) => {
+            log::warn!("Failed to parse {}: {}", manifest_path.display(), e);
+            None
+        }
+    }
+}
+
+/// `bom-ref`s are formatted like Cargo package IDs, except that only the kind of the source is known
+fn auditable_bom_ref(package: &AuditablePackage) -> String {
+    format!("{}#{}@{}", package.source, package.name, package.version)
+}
+
+fn create_auditable_component(
+    package: &AuditablePackage,
+    bom_ref: String,
+    spec_version: SpecVersion,
+) -> Component {
+    let mut component = Component::new(
+        Classification::Library,
+        &package.name,
+        &package.version,
+        Some(bom_ref),
+    );
+    if package.source == "crates.io" {
+        component.purl = match get_crates_io_purl(&package.name, Some(&package.version)) {
+            Ok(purl) => Some(purl),
+            Err(e) => {
+                log::warn!("Package {} has an invalid Purl: {} ", package.name, e);
+                None
+            }
+        };
+    }
+
+    let usage = match package.kind {
+        AuditableDependencyKind::Build => DependencyUsage::Build,
+        AuditableDependencyKind::Runtime => DependencyUsage::Normal,
+    };
+    component.scope = Some(usage.scope());
+    component.properties = Some(Properties(vec![Property::new(
+        "cdx:cargo:dependency-kind",
+        usage.as_str(),
+    )]));
+    if spec_version >= SpecVersion::V1_5 && component.purl.is_some() {
+        let method = Method {
+            technique: "binary-analysis".to_owned(),
+            confidence: ConfidenceScore::new(BINARY_CONFIDENCE),
+            value: Some("cargo auditable".to_owned()),
+        };
+        evidence_mut(&mut component).identity = Some(purl_identity(method, None));
+    }
+    component
+}
+
+/// Generates the `Dependencies` field in the final SBOM
+fn create_dependencies(resolve: &ResolveMap) -> Dependencies {
+    let deps = resolve
+        .values()
+        .map(|node| Dependency {
+            dependency_ref: node.id.to_string(),
+            dependencies: node.dependencies.iter().map(|d| d.to_string()).collect(),
+        })
+        .collect();
+    Dependencies(deps)
+}
+
+/// Adds the node to the resolve graph.
+/// If the package is already present, the dependencies of both nodes are combined.
+fn merge_node(resolve: &mut ResolveMap, node: Node) {
+    match resolve.get_mut(&node.id) {
+        Some(existing) => {
+            for dep in node.deps {
+                if !existing.deps.iter().any(|d| d.pkg == dep.pkg) {
+                    existing.dependencies.push(dep.pkg.clone());
+                    existing.deps.push(dep);
+                }
+            }
+        }
+        None => {
+            resolve.insert(node.id.clone(), node);
+        }
+    }
+}
+
+/// Selects the packages to be included in the SBOM of `root` according to the configuration,
+/// and determines how each of them is used
+fn prune_dependencies(
+    root: &PackageId,
+    packages: &PackageMap,
+    resolve: &ResolveMap,
+    config: &SbomConfig,
+) -> (PackageMap, ResolveMap, DependencyInfoMap) {
+    let include_dev = config.include_dev_dependencies();
+    let (packages, resolve) =
+        if config.included_dependencies() == IncludedDependencies::AllDependencies {
+            all_dependencies(root, packages, resolve, include_dev)
+        } else {
+            top_level_dependencies(root, packages, resolve, include_dev)
+        };
+    let dependency_info = dependency_info(root, &packages, &resolve);
+
+    (packages, resolve, dependency_info)
+}
+
+/// Determines how every package in the pruned dependency graph is used by the `root` package.
+/// The `root` package itself is not included in the output.
+fn dependency_info(
+    root: &PackageId,
+    packages: &PackageMap,
+    resolve: &ResolveMap,
+) -> DependencyInfoMap {
+    // A package may be reachable through several paths, e.g. as both a normal and a build dependency.
+    // Keep revisiting packages until the strongest relationship is found for each of them.
+    let mut usages: HashMap<&PackageId, DependencyUsage> = HashMap::new();
+    let mut queue = vec![(root, DependencyUsage::Normal)];
+    while let Some((id, usage)) = queue.pop() {
+        if usages.get(id).is_some_and(|known| *known <= usage) {
+            continue;
+        }
+        usages.insert(id, usage);
+
+        for dep in &resolve[id].deps {
+            let mut edge_usage = dep
+                .dep_kinds
+                .iter()
+                .map(|kind| match kind.kind {
+                    DependencyKind::Development => DependencyUsage::Dev,
+                    DependencyKind::Build => DependencyUsage::Build,
+                    _ => DependencyUsage::Normal,
+                })
+                .min()
+                .unwrap_or(DependencyUsage::Normal);
+            // Procedural macros are executed by the compiler and are never linked into the artifact
+            if is_proc_macro(&packages[&dep.pkg]) {
+                edge_usage = edge_usage.max(DependencyUsage::Build);
+            }
+            queue.push((&dep.pkg, usage.max(edge_usage)));
+        }
+    }
+
+    let mut depended_upon = HashSet::new();
+    let mut required = HashSet::new();
+    let mut enabled_by: HashMap<&PackageId, BTreeSet<String>> = HashMap::new();
+    let mut unconditional = HashSet::new();
+    let mut edge_conditions: HashMap<&PackageId, BTreeSet<(String, String)>> = HashMap::new();
+    for node in resolve.values() {
+        let parent = &packages[&node.id];
+        for dep in &node.deps {
+            depended_upon.insert(&dep.pkg);
+            // `dep_kinds` is only missing with very old Cargo versions that did not report targets either
+            if dep.dep_kinds.is_empty() || dep.dep_kinds.iter().any(|k| k.target.is_none()) {
+                unconditional.insert(&dep.pkg);
+            } else {
+                let conditions = edge_conditions.entry(&dep.pkg).or_default();
+                for target in dep.dep_kinds.iter().filter_map(|k| k.target.as_ref()) {
+                    conditions.insert((node.id.to_string(), target.to_string()));
+                }
+            }
+            let declarations = dependency_declarations(parent, dep, packages);
+            if declarations.is_empty() || declarations.iter().any(|d| !d.optional) {
+                required.insert(&dep.pkg);
+            }
+            for feature in enabling_features(parent, node, &declarations) {
+                let feature = format!("{}/{}", parent.name, feature);
+                enabled_by.entry(&dep.pkg).or_default().insert(feature);
+            }
+        }
+    }
+
+    usages
+        .into_iter()
+        .filter(|(id, _)| *id != root)
+        .map(|(id, usage)| {
+            let edge_conditions = edge_conditions.remove(id).unwrap_or_default();
+            let conditions = if unconditional.contains(id) {
+                BTreeSet::new()
+            } else {
+                edge_conditions.iter().map(|(_, c)| c.clone()).collect()
+            };
+            let info = DependencyInfo {
+                usage,
+                optional: depended_upon.contains(id) && !required.contains(id),
+                enabled_by: enabled_by.remove(id).unwrap_or_default(),
+                conditions,
+                edge_conditions,
+            };
+            (id.to_owned(), info)
+        })
+        .collect()
+}
+
+fn active_features(resolve: &ResolveMap) -> HashMap<PackageId, Vec<String>> {
+    resolve
+        .values()
+        .map(|node| (node.id.clone(), node.features.clone()))
+        .collect()
+}
+
+/// Finds the entries in the `Cargo.toml` of `parent` that declare the dependency
+fn dependency_declarations<'a>(
+    parent: &'a Package,
+    dep: &NodeDep,
+    packages: &PackageMap,
+) -> Vec<&'a cargo_metadata::Dependency> {
+    let dep_name = &packages[&dep.pkg].name;
+    parent
+        .dependencies
+        .iter()
+        .filter(|d| match &d.rename {
+            // `NodeDep::name` is the name of the crate as seen from Rust code
+            Some(rename) => rename.replace('-', "_") == dep.name,
+            None => &d.name == dep_name,
+        })
+        .filter(|d| dep.dep_kinds.iter().any(|k| k.kind == d.kind))
+        .collect()
+}
+
+/// Lists the active features of the `parent` package that directly enable
+/// any of the optional `declarations`
+fn enabling_features<'a>(
+    parent: &'a Package,
+    node: &'a Node,
+    declarations: &[&cargo_metadata::Dependency],
+) -> impl Iterator<Item = &'a String> + 'a {
+    // The name used in the `[features]` table, which may differ from the package name
+    let dep_keys: Vec<String> = declarations
+        .iter()
+        .filter(|d| d.optional)
+        .map(|d| d.rename.clone().unwrap_or_else(|| d.name.clone()))
+        .collect();
+    node.features.iter().filter(move |feature| {
+        let definition = parent.features.get(*feature);
+        dep_keys.iter().any(|key| {
+            // The implicit feature created for an optional dependency
+            *feature == key
+                || definition.is_some_and(|items| {
+                    items.iter().any(|item| {
+                        // `dep:foo`, `foo` and `foo/bar` enable the dependency, but `foo?/bar` does not
+                        item.strip_prefix("dep:") == Some(key)
+                            || item == key
+                            || item
+                                .strip_prefix(key.as_str())
+                                .is_some_and(|rest| rest.starts_with('/'))
+                    })
+                })
+        })
+    })
+}
+
+/// Packages that are not downloaded from a registry, and thus have no checksum in `Cargo.lock`
+fn is_local_or_git(package: &Package) -> bool {
+    match &package.source {
+        Some(source) => source.repr.starts_with("git+"),
+        None => true,
+    }
+}
 
-    #[error("Could not parse author string: {}", .0)]
-    AuthorParseError(String),
+fn is_proc_macro(package: &Package) -> bool {
+    package
+        .targets
+        .iter()
+        .any(|tgt| tgt.kind.iter().any(|kind| kind == "proc-macro"))
 }
 
-/// Generates the `Dependencies` field in the final SBOM
-fn create_dependencies(resolve: &ResolveMap) -> Dependencies {
-    let deps = resolve
-        .values()
-        .map(|node| Dependency {
-            dependency_ref: node.id.to_string(),
-            dependencies: node.dependencies.iter().map(|d| d.to_string()).collect(),
-        })
-        .collect();
-    Dependencies(deps)
+/// Removes the packages that are only used at build time from the dependency graph
+fn remove_build_dependencies(
+    packages: &mut PackageMap,
+    resolve: &mut ResolveMap,
+    dependency_info: &DependencyInfoMap,
+) {
+    let is_build_only = |id: &PackageId| {
+        dependency_info
+            .get(id)
+            .is_some_and(|info| info.usage == DependencyUsage::Build)
+    };
+    packages.retain(|id, _| !is_build_only(id));
+    resolve.retain(|id, _| !is_build_only(id));
+    for node in resolve.values_mut() {
+        node.deps.retain(|dep| !is_build_only(&dep.pkg));
+        node.dependencies.retain(|id| !is_build_only(id));
+    }
 }
 
 fn top_level_dependencies(
     root: &PackageId,
     packages: &PackageMap,
     resolve: &ResolveMap,
+    include_dev: bool,
 ) -> (PackageMap, ResolveMap) {
     log::trace!("Adding top-level dependencies to SBOM");
 
-    // Only include packages that have dependency kinds other than "Development"
-    let root_node = strip_dev_dependencies(&resolve[root]);
+    // Unless requested otherwise, only include packages that have dependency kinds other than "Development"
+    let root_node = if include_dev {
+        resolve[root].clone()
+    } else {
+        strip_dev_dependencies(&resolve[root])
+    };
 
     let mut pkg_result = PackageMap::new();
     // Record the root package, then its direct non-dev dependencies
@@ -615,6 +2144,7 @@ fn all_dependencies(
     root: &PackageId,
     packages: &PackageMap,
     resolve: &ResolveMap,
+    include_dev: bool,
 ) -> (PackageMap, ResolveMap) {
     log::trace!("Adding all dependencies to SBOM");
 
@@ -634,10 +2164,16 @@ fn all_dependencies(
         for node in current_queue.drain(..) {
             // If we haven't processed this node yet...
             if !out_resolve.contains_key(&node.id) {
-                // Add the node to the output
-                out_resolve.insert(node.id.to_owned(), strip_dev_dependencies(node));
+                // Dev-dependencies are only ever built for the root package
+                let node = if include_dev && &node.id == root {
+                    node.clone()
+                } else {
+                    strip_dev_dependencies(node)
+                };
                 // Queue its dependencies for the next BFS loop iteration
-                next_queue.extend(non_dev_dependencies(&node.deps).map(|dep| &resolve[&dep.pkg]));
+                next_queue.extend(node.deps.iter().map(|dep| &resolve[&dep.pkg]));
+                // Add the node to the output
+                out_resolve.insert(node.id.to_owned(), node);
             }
         }
         std::mem::swap(&mut current_queue, &mut next_queue);
@@ -677,6 +2213,7 @@ fn non_dev_dependencies(input: &[NodeDep]) -> impl Iterator<Item = &NodeDep> {
 /// * `package_name` - Package from which this SBOM was generated
 /// * `sbom_config` - Configuration options used during generation
 /// * `target_kinds` - Detailed information on the kinds of targets in `sbom`
+#[derive(Clone)]
 pub struct GeneratedSbom {
     pub bom: Bom,
     pub manifest_path: PathBuf,
@@ -686,22 +2223,287 @@ pub struct GeneratedSbom {
 }
 
 impl GeneratedSbom {
+    /// Merges the SBOMs generated separately for several target platforms
+    /// into a single SBOM for each package.
+    ///
+    /// Every component lists the targets it is present on as `cdx:cargo:target` properties.
+    /// A dependency relationship that only applies to some of the targets is recorded
+    /// as a `cdx:cargo:edge-targets` property on the dependency, with the value being
+    /// the `bom-ref` of the dependent component followed by a comma-separated list of targets.
+    pub fn combine_targets(per_target: Vec<(String, Vec<GeneratedSbom>)>) -> Vec<GeneratedSbom> {
+        let all_targets: Vec<String> = per_target.iter().map(|(t, _)| t.clone()).collect();
+
+        let mut packages: Vec<Vec<(String, GeneratedSbom)>> = Vec::new();
+        for (target, sboms) in per_target {
+            for sbom in sboms {
+                let existing = packages.iter_mut().find(|group| {
+                    group[0].1.manifest_path == sbom.manifest_path
+                        && group[0].1.package_name == sbom.package_name
+                });
+                match existing {
+                    Some(group) => group.push((target.clone(), sbom)),
+                    None => packages.push(vec![(target.clone(), sbom)]),
+                }
+            }
+        }
+
+        packages
+            .into_iter()
+            .map(|group| Self::combine_package_targets(group, &all_targets))
+            .collect()
+    }
+
+    fn combine_package_targets(
+        sboms: Vec<(String, GeneratedSbom)>,
+        all_targets: &[String],
+    ) -> GeneratedSbom {
+        let mut components: Vec<Component> = Vec::new();
+        let mut component_targets: Vec<Vec<String>> = Vec::new();
+        let mut dependencies: Vec<Dependency> = Vec::new();
+        let mut edge_targets: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+
+        let mut formula: Option<Formula> = None;
+        let mut result = None;
+        for (target, sbom) in sboms {
+            for other in sbom.bom.formulation.iter().flatten() {
+                match &mut formula {
+                    Some(formula) => merge_formula(formula, other),
+                    None => formula = Some(other.clone()),
+                }
+            }
+
+            for component in sbom.bom.components.iter().flat_map(|c| c.0.iter()) {
+                match components
+                    .iter()
+                    .position(|c| c.bom_ref == component.bom_ref)
+                {
+                    Some(index) => {
+                        merge_component(&mut components[index], component);
+                        component_targets[index].push(target.clone());
+                    }
+                    None => {
+                        components.push(component.clone());
+                        component_targets.push(vec![target.clone()]);
+                    }
+                }
+            }
+
+            for dependency in sbom.bom.dependencies.iter().flat_map(|d| d.0.iter()) {
+                let combined = match dependencies
+                    .iter_mut()
+                    .position(|d| d.dependency_ref == dependency.dependency_ref)
+                {
+                    Some(index) => &mut dependencies[index],
+                    None => {
+                        dependencies.push(Dependency {
+                            dependency_ref: dependency.dependency_ref.clone(),
+                            dependencies: Vec::new(),
+                        });
+                        dependencies.last_mut().unwrap()
+                    }
+                };
+                for child in &dependency.dependencies {
+                    if !combined.dependencies.contains(child) {
+                        combined.dependencies.push(child.clone());
+                    }
+                    edge_targets
+                        .entry((dependency.dependency_ref.clone(), child.clone()))
+                        .or_default()
+                        .push(target.clone());
+                }
+            }
+
+            result.get_or_insert(sbom);
+        }
+
+        for (component, targets) in components.iter_mut().zip(component_targets) {
+            let properties = component.properties.get_or_insert(Properties(Vec::new()));
+            for target in targets {
+                properties
+                    .0
+                    .push(Property::new("cdx:cargo:target", &target));
+            }
+        }
+        for ((parent, child), targets) in edge_targets {
+            if targets.len() == all_targets.len() {
+                continue;
+            }
+            let child = components
+                .iter_mut()
+                .find(|c| c.bom_ref.as_ref() == Some(&child));
+            if let Some(child) = child {
+                let value = format!("{} {}", parent, targets.join(","));
+                let properties = child.properties.get_or_insert(Properties(Vec::new()));
+                properties
+                    .0
+                    .push(Property::new("cdx:cargo:edge-targets", &value));
+            }
+        }
+
+        let mut result = result.expect("no SBOMs to combine");
+        result.bom.components = Some(Components(components));
+        result.bom.dependencies = Some(Dependencies(dependencies));
+        result.bom.formulation = formula.map(|formula| vec![formula]);
+        result.sbom_config.target = Some(Target::MultipleTargets(all_targets.to_vec()));
+        result
+    }
+
+    /// Narrows the SBOM down to what has actually been built, according to the
+    /// messages of `cargo build --message-format=json`:
+    ///
+    ///  - Only the crates that have been compiled are kept as components.
+    ///  - The `cdx:cargo:feature` properties are replaced with the features the crates have been compiled with.
+    ///  - The binaries, `cdylib`s and `staticlib`s of the described package get a SHA-256 hash of the built files.
+    ///
+    /// The SBOM is left unchanged if the messages do not mention the described package,
+    /// since they come from a different build.
+    pub fn apply_build_artifacts(&mut self, artifacts: &BuildArtifacts) {
+        let Some(root) = self
+            .bom
+            .metadata
+            .as_mut()
+            .and_then(|meta| meta.component.as_mut())
+        else {
+            return;
+        };
+        let root_ref = root.bom_ref.clone().unwrap_or_default();
+        let subcomponents = root
+            .components
+            .as_mut()
+            .map(|c| &mut c.0[..])
+            .unwrap_or(&mut []);
+        // With `--workspace-bom` the subcomponents are the members, with their own targets nested in them
+        let mut described = vec![root_ref.clone()];
+        for component in subcomponents.iter() {
+            let Some(bom_ref) = component.bom_ref.as_deref() else {
+                continue;
+            };
+            match bom_ref.rsplit_once(" bin-target-") {
+                Some((package_id, _)) => described.push(package_id.to_owned()),
+                None => described.push(bom_ref.to_owned()),
+            }
+        }
+        if !described.iter().any(|id| artifacts.is_compiled(id)) {
+            log::warn!(
+                "The build messages do not mention {}, ignoring them",
+                self.package_name
+            );
+            return;
+        }
+
+        hash_target_outputs(subcomponents, artifacts, &self.target_kinds);
+        for member in subcomponents.iter_mut() {
+            let Some(bom_ref) = member.bom_ref.clone() else {
+                continue;
+            };
+            if bom_ref.contains(" bin-target-") {
+                continue;
+            }
+            if artifacts.is_compiled(&bom_ref) {
+                set_compiled_features(member, artifacts.features(&bom_ref));
+            }
+            if let Some(targets) = member.components.as_mut() {
+                hash_target_outputs(&mut targets.0, artifacts, &self.target_kinds);
+            }
+        }
+        if artifacts.is_compiled(&root_ref) {
+            set_compiled_features(root, artifacts.features(&root_ref));
+        }
+
+        for component in self.bom.components.iter_mut().flat_map(|c| c.0.iter_mut()) {
+            let Some(bom_ref) = component.bom_ref.clone() else {
+                continue;
+            };
+            if artifacts.is_compiled(&bom_ref) {
+                set_compiled_features(component, artifacts.features(&bom_ref));
+            }
+        }
+        prune_unlinked(&mut self.bom, &artifacts.compiled_packages());
+    }
+
+    /// Records the advisories from the RustSec database that affect the components as vulnerabilities,
+    /// replacing any vulnerabilities recorded previously.
+    ///
+    /// Only crates from crates.io are matched, since the database does not cover other sources.
+    /// Vulnerabilities are only supported since CycloneDX 1.4.
+    pub fn add_vulnerabilities(&mut self, database: &AdvisoryDatabase) {
+        let spec_version = self.sbom_config.spec_version();
+        if spec_version < SpecVersion::V1_4 {
+            log::warn!(
+                "Not recording vulnerabilities of {} because they require CycloneDX 1.4 or later",
+                self.package_name
+            );
+            return;
+        }
+
+        let mut matches: Vec<(&Advisory, Vec<VulnerabilityTarget>)> = Vec::new();
+        for component in self.bom.components.iter().flat_map(|c| c.0.iter()) {
+            if !is_from_crates_io(component) {
+                continue;
+            }
+            let (Some(bom_ref), Some(version)) = (&component.bom_ref, &component.version) else {
+                continue;
+            };
+            let Ok(version) = Version::parse(version.as_ref()) else {
+                continue;
+            };
+            for advisory in database.find(component.name.as_ref(), &version) {
+                log::info!(
+                    "{} {} is affected by {}",
+                    component.name,
+                    version,
+                    advisory.id
+                );
+                let target = VulnerabilityTarget {
+                    bom_ref: bom_ref.clone(),
+                    versions: Some(Versions(vec![TargetVersion::new(
+                        &version.to_string(),
+                        "affected",
+                    )])),
+                };
+                match matches.iter_mut().find(|(a, _)| a.id == advisory.id) {
+                    Some((_, targets)) => targets.push(target),
+                    None => matches.push((advisory, vec![target])),
+                }
+            }
+        }
+
+        self.bom.vulnerabilities = if matches.is_empty() {
+            None
+        } else {
+            Some(Vulnerabilities(
+                matches
+                    .into_iter()
+                    .map(|(advisory, targets)| {
+                        create_vulnerability(advisory, targets, spec_version)
+                    })
+                    .collect(),
+            ))
+        };
+    }
+
     /// Writes SBOM to either a JSON or XML file in the same folder as `Cargo.toml` manifest
     pub fn write_to_files(self) -> Result<(), SbomWriterError> {
         match self.sbom_config.describe.unwrap_or_default() {
             Describe::Crate => {
                 let path = self.manifest_path.with_file_name(self.filename(None, &[]));
-                Self::write_to_file(self.bom, &path, &self.sbom_config)
+                let bom = Self::finalize(self.bom, &self.manifest_path, &self.sbom_config)?;
+                Self::write_to_file(bom, &path, &self.sbom_config)
             }
             pattern @ (Describe::Binaries | Describe::AllCargoTargets) => {
-                for (sbom, target_kind) in
-                    Self::per_artifact_sboms(&self.bom, &self.target_kinds, pattern)
-                {
+                let unit_graph = self.unit_graph();
+                for (sbom, target_kind) in Self::per_artifact_sboms(
+                    &self.bom,
+                    &self.target_kinds,
+                    pattern,
+                    unit_graph.as_ref(),
+                ) {
                     let meta = sbom.metadata.as_ref().unwrap();
                     let name = meta.component.as_ref().unwrap().name.as_ref();
                     let path = self
                         .manifest_path
                         .with_file_name(self.filename(Some(name), &target_kind));
+                    let sbom = Self::finalize(sbom, &self.manifest_path, &self.sbom_config)?;
                     Self::write_to_file(sbom, &path, &self.sbom_config)?;
                 }
                 Ok(())
@@ -709,10 +2511,32 @@ impl GeneratedSbom {
         }
     }
 
+    /// Applies the normalization of `--reproducible` to a SBOM that is about to be written.
+    /// Local paths are recorded relative to the directory the SBOM is written to.
+    fn finalize(
+        mut bom: Bom,
+        manifest_path: &Path,
+        config: &SbomConfig,
+    ) -> Result<Bom, SbomWriterError> {
+        if !config.reproducible() {
+            return Ok(bom);
+        }
+
+        let base_dir = manifest_path.parent().unwrap_or(Path::new(""));
+        reproducible::normalize(&mut bom, base_dir)?;
+
+        let mut canonical = Vec::new();
+        Self::write_json(bom.clone(), config.spec_version(), &mut canonical)?;
+        bom.serial_number = Some(reproducible::content_serial_number(&canonical));
+        Ok(bom)
+    }
+
     fn write_to_file(bom: Bom, path: &Path, config: &SbomConfig) -> Result<(), SbomWriterError> {
+        let spec_version = config.spec_version();
+
         // If running in debug mode, validate that the SBOM is self-consistent and well-formed
         if cfg!(debug_assertions) {
-            let result = bom.validate();
+            let result = bom.validate_version(spec_version);
             if result.has_errors() {
                 panic!(
                     "The generated SBOM failed validation: {:?}",
@@ -722,25 +2546,18 @@ impl GeneratedSbom {
         }
 
         use cyclonedx_bom::models::bom::SpecVersion::*;
-        let spec_version = config.spec_version.unwrap_or(V1_3);
 
         log::info!("Outputting {}", path.display());
         let file = File::create(path)?;
         let mut writer = BufWriter::new(file);
         match config.format() {
-            Format::Json => {
-                match spec_version {
-                    V1_3 => bom.output_as_json_v1_3(&mut writer),
-                    V1_4 => bom.output_as_json_v1_4(&mut writer),
-                    _ => unimplemented!(),
-                }
-                .map_err(SbomWriterError::JsonWriteError)?;
-            }
+            Format::Json => Self::write_json(bom, spec_version, &mut writer)?,
             Format::Xml => {
                 match spec_version {
                     V1_3 => bom.output_as_xml_v1_3(&mut writer),
                     V1_4 => bom.output_as_xml_v1_4(&mut writer),
-                    _ => unimplemented!(),
+                    V1_5 => bom.output_as_xml_v1_5(&mut writer),
+                    version => return Err(SbomWriterError::UnsupportedSpecVersion(version)),
                 }
                 .map_err(SbomWriterError::XmlWriteError)?;
             }
@@ -752,11 +2569,70 @@ impl GeneratedSbom {
         Ok(())
     }
 
+    fn write_json<W: std::io::Write>(
+        bom: Bom,
+        spec_version: SpecVersion,
+        writer: &mut W,
+    ) -> Result<(), SbomWriterError> {
+        use cyclonedx_bom::models::bom::SpecVersion::*;
+        match spec_version {
+            V1_3 => bom.output_as_json_v1_3(writer),
+            V1_4 => bom.output_as_json_v1_4(writer),
+            V1_5 => bom.output_as_json_v1_5(writer),
+            version => return Err(SbomWriterError::UnsupportedSpecVersion(version)),
+        }
+        .map_err(SbomWriterError::JsonWriteError)
+    }
+
+    /// Embeds the SBOM of every binary into the ELF file of the same name in `directory`,
+    /// such as `target/release`. Binaries that have not been built are skipped with a warning.
+    ///
+    /// The SBOMs are the same as the ones written with `--describe binaries`,
+    /// and are always serialized as JSON.
+    pub fn embed_into_binaries(&self, directory: &Path) -> Result<(), SbomWriterError> {
+        let unit_graph = self.unit_graph();
+        for (sbom, target_kind) in Self::per_artifact_sboms(
+            &self.bom,
+            &self.target_kinds,
+            Describe::Binaries,
+            unit_graph.as_ref(),
+        ) {
+            let name = sbom
+                .metadata
+                .as_ref()
+                .and_then(|meta| meta.component.as_ref())
+                .map(|component| component.name.to_string())
+                .unwrap();
+            let filename = if target_kind.contains(&"bin".to_owned()) {
+                name
+            } else {
+                // cdylib
+                format!("lib{}.so", name.replace('-', "_"))
+            };
+            let path = directory.join(filename);
+            if !path.is_file() {
+                log::warn!(
+                    "Not embedding the SBOM into {} because it does not exist",
+                    path.display()
+                );
+                continue;
+            }
+
+            let sbom = Self::finalize(sbom, &self.manifest_path, &self.sbom_config)?;
+            let mut json = Vec::new();
+            Self::write_json(sbom, self.sbom_config.spec_version(), &mut json)?;
+            log::info!("Embedding the SBOM into {}", path.display());
+            embed_into_elf(&path, &json)?;
+        }
+        Ok(())
+    }
+
     /// Returns an iterator over SBOMs and their associated target kinds
     fn per_artifact_sboms<'a>(
         bom: &'a Bom,
         target_kinds: &'a TargetKinds,
         describe: Describe,
+        unit_graph: Option<&'a UnitGraph>,
     ) -> impl Iterator<Item = (Bom, Vec<String>)> + 'a {
         let meta = bom.metadata.as_ref().unwrap();
         let crate_component = meta.component.as_ref().unwrap();
@@ -779,8 +2655,26 @@ impl GeneratedSbom {
                     Describe::Crate => unreachable!(),
                 }
             })
-            .map(|component| {
-                let target_kind = &target_kinds.0[component.bom_ref.as_ref().unwrap()];
+            .filter_map(move |component| {
+                let bom_ref = component.bom_ref.as_ref().unwrap();
+                let target_kind = &target_kinds.0[bom_ref];
+                let linked = match unit_graph {
+                    Some(unit_graph) => {
+                        let (package_id, _) = bom_ref.rsplit_once(" bin-target-").unwrap();
+                        match unit_graph.linked_packages(package_id, &component.name, target_kind)
+                        {
+                            Some(linked) => Some(linked),
+                            None => {
+                                log::warn!(
+                                    "Not describing target {} because it is not built with the selected features",
+                                    component.name
+                                );
+                                return None;
+                            }
+                        }
+                    }
+                    None => None,
+                };
                 // In the original SBOM the toplevel component describes a crate.
                 // We need to change it to describe a specific binary.
                 // Most properties apply to the entire package and should be kept;
@@ -791,11 +2685,32 @@ impl GeneratedSbom {
                 toplevel_component.name = component.name.clone();
                 toplevel_component.component_type = component.component_type.clone();
                 toplevel_component.purl.clone_from(&component.purl);
+                if let Some(linked) = linked {
+                    prune_unlinked(&mut new_bom, &linked);
+                }
 
-                (new_bom, target_kind.clone())
+                Some((new_bom, target_kind.clone()))
             })
     }
 
+    /// Computes the unit graph used to narrow down the dependencies of every target,
+    /// or `None` if it is not enabled or not available, in which case each target lists all dependencies of the package.
+    fn unit_graph(&self) -> Option<UnitGraph> {
+        if !self.sbom_config.unit_graph() {
+            return None;
+        }
+        match UnitGraph::query(&self.manifest_path, &self.sbom_config) {
+            Ok(unit_graph) => unit_graph,
+            Err(e) => {
+                log::warn!(
+                    "Could not determine the dependencies of each target, listing all dependencies of the package instead: {}",
+                    e
+                );
+                None
+            }
+        }
+    }
+
     fn filename(&self, binary_name: Option<&str>, target_kind: &[String]) -> String {
         let output_options = self.sbom_config.output_options();
         let describe = self.sbom_config.describe.unwrap_or_default();
@@ -846,23 +2761,401 @@ impl GeneratedSbom {
     }
 }
 
-/// Locates the corresponding `Cargo.lock` file given the location of `Cargo.toml`.
-/// This must be run **after** `cargo metadata` which will generate the `Cargo.lock` file
-/// and make sure it's up to date.
-fn locate_cargo_lock(manifest_path: &Path) -> Result<PathBuf, std::io::Error> {
-    let manifest_path = manifest_path.canonicalize()?;
-    let ancestors = manifest_path.as_path().ancestors();
+/// Records the hashes of the files built for the components describing the targets of a package.
+/// Components that do not describe a target, such as workspace members, are skipped.
+fn hash_target_outputs(
+    components: &mut [Component],
+    artifacts: &BuildArtifacts,
+    target_kinds: &TargetKinds,
+) {
+    for component in components.iter_mut() {
+        let Some(bom_ref) = component.bom_ref.as_ref() else {
+            continue;
+        };
+        let (Some((package_id, _)), Some(target_kind)) = (
+            bom_ref.rsplit_once(" bin-target-"),
+            target_kinds.0.get(bom_ref),
+        ) else {
+            continue;
+        };
+        let mut hashes = Vec::new();
+        for file in artifacts.output_files(package_id, &component.name, target_kind) {
+            match artifact_hash(file) {
+                Ok(hash) => hashes.push(hash),
+                Err(e) => log::warn!("Failed to hash {}: {}", file, e),
+            }
+        }
+        if !hashes.is_empty() {
+            component.hashes = Some(cyclonedx_bom::models::hash::Hashes(hashes));
+        }
+    }
+}
+
+/// Combines the information about a component gathered for different target platforms
+fn merge_component(existing: &mut Component, other: &Component) {
+    if other.scope == Some(Scope::Required) {
+        existing.scope = Some(Scope::Required);
+    }
+    if let Some(other_properties) = &other.properties {
+        let properties = existing.properties.get_or_insert(Properties(Vec::new()));
+        for property in &other_properties.0 {
+            if !properties.0.contains(property) {
+                properties.0.push(property.clone());
+            }
+        }
+    }
+}
+
+/// Removes the components, dependencies and vulnerabilities of packages other than `linked`,
+/// e.g. the ones that are not linked into a target or have not been compiled.
+/// Native libraries are kept along with the crate that declares them.
+fn prune_unlinked(bom: &mut Bom, linked: &BTreeSet<String>) {
+    let is_linked = |bom_ref: &str| {
+        linked.contains(bom_ref)
+            || bom_ref
+                .rsplit_once(" links:")
+                .is_some_and(|(crate_ref, _)| linked.contains(crate_ref))
+    };
+    let root_ref = bom
+        .metadata
+        .as_ref()
+        .and_then(|meta| meta.component.as_ref())
+        .and_then(|component| component.bom_ref.clone());
+    let keep = |bom_ref: &str| is_linked(bom_ref) || root_ref.as_deref() == Some(bom_ref);
+
+    if let Some(components) = &mut bom.components {
+        components
+            .0
+            .retain(|component| component.bom_ref.as_deref().is_some_and(keep));
+    }
+    if let Some(dependencies) = &mut bom.dependencies {
+        dependencies
+            .0
+            .retain(|dependency| keep(&dependency.dependency_ref));
+        for dependency in &mut dependencies.0 {
+            dependency.dependencies.retain(|dep| keep(dep));
+        }
+    }
+    if let Some(vulnerabilities) = &mut bom.vulnerabilities {
+        for vulnerability in &mut vulnerabilities.0 {
+            if let Some(targets) = &mut vulnerability.vulnerability_targets {
+                targets.0.retain(|target| keep(&target.bom_ref));
+            }
+        }
+        vulnerabilities.0.retain(|vulnerability| {
+            vulnerability
+                .vulnerability_targets
+                .as_ref()
+                .map_or(true, |targets| !targets.0.is_empty())
+        });
+    }
+}
+
+/// Replaces the `cdx:cargo:feature` properties of a component with the features it has been compiled with
+fn set_compiled_features(component: &mut Component, features: BTreeSet<String>) {
+    let properties = component.properties.get_or_insert(Properties(Vec::new()));
+    properties
+        .0
+        .retain(|property| property.name != "cdx:cargo:feature");
+    for feature in &features {
+        properties
+            .0
+            .push(Property::new("cdx:cargo:feature", feature));
+    }
+    if properties.0.is_empty() {
+        component.properties = None;
+    }
+}
+
+/// Confidence in the identity of a crate reported by `cargo metadata`, which resolves the dependencies itself
+const MANIFEST_CONFIDENCE: f32 = 0.9;
+/// Confidence in the identity of a crate read from `Cargo.lock` alone, which may be out of date
+const LOCKFILE_CONFIDENCE: f32 = 0.7;
+/// Confidence in the identity of a crate recorded in a binary by `cargo auditable`
+const BINARY_CONFIDENCE: f32 = 0.8;
+
+/// Confidence in the contents of a registry crate, which Cargo verifies against the checksum in `Cargo.lock`
+const CHECKSUM_CONFIDENCE: f32 = 1.0;
+
+/// Identity evidence for the purl of a component, as confident as the method it was identified with.
+///
+/// Registry crates also record the comparison of their contents with the `checksum` from `Cargo.lock`.
+/// It does not add to the confidence, since the checksum comes from the same `Cargo.lock`
+/// as the crate it is meant to confirm.
+fn purl_identity(identification: Method, checksum: Option<&Checksum>) -> Identity {
+    let confidence = identification.confidence.clone();
+    let mut methods = vec![identification];
+    methods.extend(checksum.map(checksum_comparison));
+    Identity {
+        field: IdentityField::Purl,
+        confidence: Some(confidence),
+        methods: Some(Methods(methods)),
+        tools: None,
+    }
+}
+
+fn manifest_analysis(source: &str, confidence: f32) -> Method {
+    Method {
+        technique: "manifest-analysis".to_owned(),
+        confidence: ConfidenceScore::new(confidence),
+        value: Some(source.to_owned()),
+    }
+}
+
+/// Cargo verifies the contents of every crate downloaded from a registry against the checksum in `Cargo.lock`
+fn checksum_comparison(checksum: &Checksum) -> Method {
+    Method {
+        technique: "hash-comparison".to_owned(),
+        confidence: ConfidenceScore::new(CHECKSUM_CONFIDENCE),
+        value: Some(format!("{checksum:x}")),
+    }
+}
+
+fn evidence_mut(component: &mut Component) -> &mut ComponentEvidence {
+    component.evidence.get_or_insert(ComponentEvidence {
+        licenses: None,
+        copyright: None,
+        occurrences: None,
+        callstack: None,
+        identity: None,
+    })
+}
+
+/// Records the license and notice files found in the package along with their texts,
+/// except for the text of `attached_file`, which is already attached to the declared license.
+/// Notice files do not name a license, so they are recorded as `NOASSERTION`.
+fn create_license_evidence(
+    license_files: &[LicenseFile],
+    attached_file: Option<&Utf8Path>,
+) -> Option<Licenses> {
+    let licenses: Vec<LicenseChoice> = license_files
+        .iter()
+        .map(|file| {
+            let mut license = match &file.license {
+                Some(id) => file_license(id),
+                None => License::named_license("NOASSERTION"),
+            };
+            if attached_file != Some(file.path.as_path()) {
+                license.text = Some(AttachedText::new(None, file.text.clone()));
+            }
+            let name = match file.kind {
+                LicenseFileKind::License => "cdx:cargo:license-file",
+                LicenseFileKind::Notice => "cdx:cargo:notice-file",
+            };
+            let mut properties = vec![Property::new(name, &file.relative_path)];
+            if let Some(confidence) = file.confidence {
+                properties.push(Property::new(
+                    "cdx:cargo:license-confidence",
+                    &format!("{confidence:.2}"),
+                ));
+            }
+            license.properties = Some(Properties(properties));
+            LicenseChoice::License(license)
+        })
+        .collect();
+    if licenses.is_empty() {
+        None
+    } else {
+        Some(Licenses(licenses))
+    }
+}
+
+/// The license identified for a license file, named if the identifier is not on the SPDX list
+fn file_license(id: &str) -> License {
+    match SpdxIdentifier::try_from(id.to_owned()) {
+        Ok(_) => License::license_id(id),
+        Err(_) => License::named_license(id),
+    }
+}
+
+/// Packages from crates.io are the only ones whose PURL has no qualifiers
+fn is_from_crates_io(component: &Component) -> bool {
+    component
+        .purl
+        .as_ref()
+        .is_some_and(|purl| !purl.as_ref().contains('?'))
+}
+
+fn create_vulnerability(
+    advisory: &Advisory,
+    targets: Vec<VulnerabilityTarget>,
+    spec_version: SpecVersion,
+) -> Vulnerability {
+    let mut vulnerability = Vulnerability::new(Some(advisory.id.clone()));
+    vulnerability.id = Some(NormalizedString::new(&advisory.id));
+    vulnerability.vulnerability_source = Some(VulnerabilitySource::new(
+        Some("RustSec".to_owned()),
+        Some(Uri::new(&advisory.rustsec_url())),
+    ));
+
+    let references: Vec<VulnerabilityReference> = advisory
+        .aliases
+        .iter()
+        .map(|alias| VulnerabilityReference::new(alias, alias_source(alias)))
+        .collect();
+    if !references.is_empty() {
+        vulnerability.vulnerability_references = Some(VulnerabilityReferences(references));
+    }
+    vulnerability.vulnerability_ratings = advisory
+        .cvss
+        .as_deref()
+        .and_then(|vector| cvss_rating(vector, spec_version))
+        .map(|rating| VulnerabilityRatings(vec![rating]));
+
+    if !advisory.title.is_empty() {
+        vulnerability.description = Some(advisory.title.clone());
+    }
+    if !advisory.description.is_empty() {
+        vulnerability.detail = Some(advisory.description.clone());
+    }
+    if !advisory.patched.is_empty() {
+        let patched: Vec<String> = advisory.patched.iter().map(|req| req.to_string()).collect();
+        vulnerability.recommendation = Some(format!(
+            "Upgrade to a version matching {}",
+            patched.join(" or ")
+        ));
+    }
+
+    let mut advisories: Vec<CdxAdvisory> = Vec::new();
+    for url in advisory.url.iter().chain(advisory.references.iter()) {
+        if !advisories.iter().any(|a| a.url.as_ref() == url) {
+            advisories.push(CdxAdvisory::new(Uri::new(url)));
+        }
+    }
+    if !advisories.is_empty() {
+        vulnerability.advisories = Some(Advisories(advisories));
+    }
+    vulnerability.published = DateTime::try_from(format!("{}T00:00:00Z", advisory.date)).ok();
+    vulnerability.vulnerability_targets = Some(VulnerabilityTargets(targets));
+
+    vulnerability
+}
+
+/// Returns the database that assigned an alias of a RustSec advisory
+fn alias_source(alias: &str) -> VulnerabilitySource {
+    if alias.starts_with("CVE-") {
+        VulnerabilitySource::new(
+            Some("NVD".to_owned()),
+            Some(Uri::new(&format!(
+                "https://nvd.nist.gov/vuln/detail/{alias}"
+            ))),
+        )
+    } else if alias.starts_with("GHSA-") {
+        VulnerabilitySource::new(
+            Some("GitHub".to_owned()),
+            Some(Uri::new(&format!("https://github.com/advisories/{alias}"))),
+        )
+    } else {
+        VulnerabilitySource::new(None, None)
+    }
+}
+
+/// Creates a rating from a CVSS vector.
+/// The score is only computed for CVSS v3, and CVSS v4 vectors require CycloneDX 1.5.
+fn cvss_rating(vector: &str, spec_version: SpecVersion) -> Option<VulnerabilityRating> {
+    let method = match vector.split('/').next()? {
+        "CVSS:3.0" => ScoreMethod::CVSSv3,
+        "CVSS:3.1" => ScoreMethod::CVSSv31,
+        "CVSS:4.0" if spec_version >= SpecVersion::V1_5 => ScoreMethod::CVSSv4,
+        _ => return None,
+    };
+    let score = cvss3_base_score(vector);
+    let severity = score.map(|score| match score {
+        s if s >= 9.0 => Severity::Critical,
+        s if s >= 7.0 => Severity::High,
+        s if s >= 4.0 => Severity::Medium,
+        s if s > 0.0 => Severity::Low,
+        _ => Severity::None,
+    });
+    let mut rating =
+        VulnerabilityRating::new(score.and_then(Score::from_f32), severity, Some(method));
+    rating.vector = Some(NormalizedString::new(vector));
+    Some(rating)
+}
+
+/// Describes the toolchain and the build configuration as formulation, which was added in CycloneDX 1.5.
+/// The toolchain is detected the first time it is needed.
+fn create_formulation(
+    toolchain: &OnceCell<Option<Toolchain>>,
+    workspace_root: &Utf8Path,
+    config: &SbomConfig,
+) -> Option<Vec<Formula>> {
+    if config.spec_version() < SpecVersion::V1_5 {
+        return None;
+    }
+    let toolchain = toolchain
+        .get_or_init(|| match Toolchain::detect() {
+            Ok(toolchain) => Some(toolchain),
+            Err(e) => {
+                log::warn!("Not recording the toolchain in the formulation: {}", e);
+                None
+            }
+        })
+        .as_ref()?;
+
+    let target = match &config.target {
+        Some(Target::SingleTarget(target)) => Some(target.as_str()),
+        _ => None,
+    };
+    let profile_settings = match config.profile() {
+        Some(profile) => read_profile_settings(workspace_root, profile),
+        None => Vec::new(),
+    };
+    // Variables that are not valid Unicode cannot be recorded
+    let environment = std::env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)));
+    Some(vec![create_formula(
+        toolchain,
+        config,
+        target,
+        &profile_settings,
+        environment,
+    )])
+}
+
+/// Verifies the sources of every package in the dependency graph that has been vendored with `cargo vendor`.
+/// Returns an error if any of them have been modified.
+fn verify_vendored_sources(
+    packages: &PackageMap,
+    resolve: &ResolveMap,
+    crate_hashes: &HashMap<cargo_metadata::PackageId, Checksum>,
+) -> Result<HashMap<PackageId, VendoredSource>, VendorError> {
+    let mut vendored_sources = HashMap::new();
+    // Local packages are never vendored
+    for package in resolve
+        .keys()
+        .map(|id| &packages[id])
+        .filter(|p| p.source.is_some())
+    {
+        if let Some(vendored) = verify_vendored_source(package, crate_hashes.get(&package.id))? {
+            vendored_sources.insert(package.id.clone(), vendored);
+        }
+    }
+    Ok(vendored_sources)
+}
 
-    for path in ancestors {
-        let potential_lockfile = path.join("Cargo.lock");
-        if potential_lockfile.is_file() {
-            return Ok(potential_lockfile);
+/// Reads the package hashes from the `Cargo.lock` file corresponding to the given `Cargo.toml`.
+/// Failures are not fatal: the SBOM is simply emitted without hashes.
+fn load_lockfile(manifest_path: &Path) -> Option<Lockfile> {
+    match locate_cargo_lock(manifest_path) {
+        Ok(path) => match read_lockfile(&path) {
+            Ok(lockfile_contents) => Some(lockfile_contents),
+            Err(err) => {
+                log::warn!(
+                    "Failed to parse `Cargo.lock`: {err}\n\
+                    Hashes and `[replace]` overrides will not be included in the SBOM."
+                );
+                None
+            }
+        },
+        Err(err) => {
+            log::warn!(
+                "Failed to locate `Cargo.lock`: {err}\n\
+                Hashes and `[replace]` overrides will not be included in the SBOM."
+            );
+            None
         }
     }
-    Err(std::io::Error::new(
-        std::io::ErrorKind::NotFound,
-        "Could not find Cargo.lock in any parent directories",
-    ))
 }
 
 /// Extracts all available package hashes from the provided `Cargo.lock` file
@@ -915,6 +3208,15 @@ pub enum SbomWriterError {
 
     #[error("Error serializing to XML")]
     SerializeXmlError(#[source] std::io::Error),
+
+    #[error("Unsupported CycloneDX specification version: {0}")]
+    UnsupportedSpecVersion(SpecVersion),
+
+    #[error("Error embedding the SBOM into a binary")]
+    EmbedError(#[from] EmbedError),
+
+    #[error("Error making the SBOM reproducible")]
+    ReproducibleError(#[from] ReproducibleError),
 }
 
 impl From<std::io::Error> for SbomWriterError {
@@ -965,4 +3267,28 @@ mod test {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn identity_is_as_confident_as_its_identification() {
+        let checksum = Checksum::Sha256([0xab; 32]);
+        let identity = purl_identity(
+            manifest_analysis("Cargo.lock", LOCKFILE_CONFIDENCE),
+            Some(&checksum),
+        );
+
+        assert_eq!(identity.field, IdentityField::Purl);
+        assert_eq!(
+            identity.confidence,
+            Some(ConfidenceScore::new(LOCKFILE_CONFIDENCE))
+        );
+        let methods = identity.methods.unwrap().0;
+        assert_eq!(methods[0].technique, "manifest-analysis");
+        assert_eq!(methods[0].value.as_deref(), Some("Cargo.lock"));
+        assert_eq!(methods[1].technique, "hash-comparison");
+        assert_eq!(
+            methods[1].confidence,
+            ConfidenceScore::new(CHECKSUM_CONFIDENCE)
+        );
+        assert_eq!(methods[1].value.as_deref(), Some("ab".repeat(32).as_str()));
+    }
 }
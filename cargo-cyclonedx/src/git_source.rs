@@ -0,0 +1,149 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Information about packages fetched from git repositories.
+
+use cargo_lock::SourceId;
+use cargo_metadata::Package;
+
+/// The repository and revision a git dependency was fetched from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitSource {
+    /// The repository URL, without the reference and commit
+    pub repository: String,
+    /// The branch, tag or revision requested in `Cargo.toml`, formatted the same way as in `Cargo.lock`,
+    /// e.g. `branch=main`. `None` if the head of the default branch was requested.
+    pub reference: Option<String>,
+    /// The full hash of the commit locked in `Cargo.lock`
+    pub commit: Option<String>,
+}
+
+impl GitSource {
+    /// Returns `None` if the package does not come from a git repository
+    pub fn from_package(package: &Package) -> Option<Self> {
+        let source = package.source.as_ref()?;
+        if !source.repr.starts_with("git+") {
+            return None;
+        }
+        // `cargo metadata` reports the same source URL as the one recorded in `Cargo.lock`
+        let source_id = match SourceId::from_url(&source.repr) {
+            Ok(source_id) => source_id,
+            Err(e) => {
+                log::warn!("Package {} has an invalid git source: {}", package.id, e);
+                return None;
+            }
+        };
+        Some(Self {
+            repository: source_id.url().to_string(),
+            reference: source_id
+                .git_reference()
+                .and_then(|r| r.pretty_ref())
+                .map(|r| r.to_string()),
+            commit: source_id.precise().map(|s| s.to_owned()),
+        })
+    }
+
+    /// A URL pointing to the exact revision, in the same format as the PURL `vcs_url` qualifier
+    pub fn revision_url(&self) -> String {
+        match &self.commit {
+            Some(commit) => format!("git+{}@{}", self.repository, commit),
+            None => format!("git+{}", self.repository),
+        }
+    }
+
+    /// Whether the package appears to be a fork of a crate published to crates.io.
+    ///
+    /// A fork keeps the `repository` field of the upstream crate in its `Cargo.toml`,
+    /// so it points elsewhere than the repository the package was fetched from.
+    pub fn is_fork_of_published_crate(&self, package: &Package) -> bool {
+        let Some(upstream) = &package.repository else {
+            return false;
+        };
+        // `publish = false` means the upstream crate never made it to crates.io
+        if package
+            .publish
+            .as_ref()
+            .is_some_and(|registries| registries.is_empty())
+        {
+            return false;
+        }
+        let upstream = normalize_repository_url(upstream);
+        let fetched_from = normalize_repository_url(&self.repository);
+        // The `repository` field of a crate in a subdirectory often points to that subdirectory
+        !(upstream.starts_with(&fetched_from) || fetched_from.starts_with(&upstream))
+    }
+}
+
+/// Strips the parts of a repository URL that do not affect which repository it refers to
+pub(crate) fn normalize_repository_url(url: &str) -> String {
+    let url = url.trim().to_lowercase();
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let url = url.split_once("://").map_or(url, |(_scheme, rest)| rest);
+    let url = match url.split_once('@') {
+        Some((user, rest)) if !user.contains('/') => rest,
+        _ => url,
+    };
+    let url = url.trim_end_matches('/');
+    url.strip_suffix(".git").unwrap_or(url).to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIT_PACKAGE_JSON: &str = include_str!("../tests/fixtures/git_package.json");
+
+    #[test]
+    fn git_source() {
+        let mut package: Package = serde_json::from_str(GIT_PACKAGE_JSON).unwrap();
+        let source = GitSource::from_package(&package).unwrap();
+        assert_eq!(
+            source.repository,
+            "https://github.com/rust-secure-code/cargo-auditable.git"
+        );
+        assert_eq!(source.reference, None);
+        assert_eq!(
+            source.commit.as_deref(),
+            Some("da85607fb1a09435d77288ccf05a92b2e8ec3f71")
+        );
+        assert_eq!(
+            source.revision_url(),
+            "git+https://github.com/rust-secure-code/cargo-auditable.git@da85607fb1a09435d77288ccf05a92b2e8ec3f71"
+        );
+        assert!(!source.is_fork_of_published_crate(&package));
+
+        package.source = Some(cargo_metadata::Source {
+            repr: "git+https://github.com/someone/cargo-auditable?branch=fix#da85607fb1a09435d77288ccf05a92b2e8ec3f71".to_owned(),
+        });
+        let source = GitSource::from_package(&package).unwrap();
+        assert_eq!(source.reference.as_deref(), Some("branch=fix"));
+        assert!(source.is_fork_of_published_crate(&package));
+    }
+
+    #[test]
+    fn repository_url_normalization() {
+        assert_eq!(
+            normalize_repository_url("https://GitHub.com/rust-lang/cargo.git/"),
+            "github.com/rust-lang/cargo"
+        );
+        assert_eq!(
+            normalize_repository_url("ssh://git@github.com/rust-lang/cargo?rev=abc"),
+            "github.com/rust-lang/cargo"
+        );
+    }
+}
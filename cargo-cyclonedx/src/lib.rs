@@ -16,11 +16,26 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
+pub mod advisory_db;
+pub mod auditable;
+pub mod build_messages;
 pub mod config;
+pub mod copyright;
+pub mod embed;
 pub mod format;
 pub mod generator;
+pub mod git_source;
+pub mod license_files;
+pub mod lockfile;
+pub mod native;
+pub mod overrides;
 pub mod platform;
 pub mod purl;
+pub mod reproducible;
+pub mod source_hash;
+pub mod toolchain;
+pub mod unit_graph;
 pub mod urlencode;
+pub mod vendor;
 
 pub use crate::generator::*;
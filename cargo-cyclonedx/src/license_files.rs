@@ -0,0 +1,308 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Discovery of license and notice files shipped in the package sources,
+//! whether or not they are declared with `license-file` in `Cargo.toml`.
+
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
+use cyclonedx_bom::license_text::identify_license;
+
+/// Extensions that license files commonly have, which are ignored when classifying them
+const TEXT_EXTENSIONS: &[&str] = &["txt", "md", "markdown", "rst", "html"];
+
+/// The directory holding one file per license, named after its SPDX identifier,
+/// as recommended by the [REUSE specification](https://reuse.software/spec/)
+const LICENSES_DIR: &str = "LICENSES";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LicenseFileKind {
+    /// The text of a license, e.g. `LICENSE-MIT` or `COPYING`
+    License,
+    /// Attribution notices that must be preserved, e.g. `NOTICE` or `COPYRIGHT`
+    Notice,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LicenseFile {
+    pub path: Utf8PathBuf,
+    /// The path relative to the package directory, using `/` as the separator
+    pub relative_path: String,
+    pub kind: LicenseFileKind,
+    /// The SPDX identifier of the license, identified from the text of the file
+    /// or suggested by its name, e.g. `MIT` for `LICENSE-MIT`
+    pub license: Option<String>,
+    /// The confidence of identifying the license from the text,
+    /// or `None` if the license is only suggested by the name of the file
+    pub confidence: Option<f32>,
+    pub text: String,
+}
+
+/// Finds the license and notice files in the root of the package directory and in its `LICENSES` directory.
+/// Files that are not valid UTF-8 are skipped.
+///
+/// The license in the root directory is identified from the text of the file if possible,
+/// and otherwise from its name. Files in the `LICENSES` directory are named after their license.
+pub fn find_license_files(package_dir: &Utf8Path) -> Result<Vec<LicenseFile>, std::io::Error> {
+    let mut files = Vec::new();
+    for (path, relative_path) in list_candidates(package_dir)? {
+        let file_name = path.file_name().unwrap_or_default();
+        let in_licenses_dir = relative_path.starts_with(&format!("{LICENSES_DIR}/"));
+        let classified = if in_licenses_dir {
+            let id = path.file_stem().unwrap_or_default();
+            Some((LicenseFileKind::License, Some(id.to_owned())))
+        } else {
+            classify(file_name)
+        };
+        let Some((kind, license)) = classified else {
+            continue;
+        };
+        let Some(mut file) = read_license_file(path, relative_path, kind)? else {
+            continue;
+        };
+        if in_licenses_dir || file.license.is_none() {
+            file.license = license;
+            file.confidence = None;
+        }
+        files.push(file);
+    }
+    Ok(files)
+}
+
+/// Reads a license file, identifying the license from its text.
+/// Returns `None` if the file is not valid UTF-8.
+pub fn read_license_file(
+    path: Utf8PathBuf,
+    relative_path: String,
+    kind: LicenseFileKind,
+) -> Result<Option<LicenseFile>, std::io::Error> {
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+            log::debug!("Skipping {} because it is not valid UTF-8", path);
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+    let identified = match kind {
+        LicenseFileKind::License => identify_license(&text).filter(|m| m.is_confident()),
+        LicenseFileKind::Notice => None,
+    };
+    Ok(Some(LicenseFile {
+        path,
+        relative_path,
+        kind,
+        license: identified.as_ref().map(|m| m.identifier.to_string()),
+        confidence: identified.map(|m| m.confidence),
+        text,
+    }))
+}
+
+/// Lists the files in the package directory and its `LICENSES` directory, sorted by path
+fn list_candidates(package_dir: &Utf8Path) -> Result<Vec<(Utf8PathBuf, String)>, std::io::Error> {
+    let mut candidates = Vec::new();
+    for entry in package_dir.read_dir_utf8()? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_file() {
+            candidates.push((entry.path().to_owned(), entry.file_name().to_owned()));
+        } else if file_type.is_dir() && entry.file_name() == LICENSES_DIR {
+            for license in entry.path().read_dir_utf8()? {
+                let license = license?;
+                if license.file_type()?.is_file() {
+                    let relative_path = format!("{}/{}", LICENSES_DIR, license.file_name());
+                    candidates.push((license.path().to_owned(), relative_path));
+                }
+            }
+        }
+    }
+    candidates.sort();
+    Ok(candidates)
+}
+
+/// Tells license and notice files apart by their conventional names,
+/// along with the license suggested by the suffix of the name, e.g. `LICENSE-APACHE`
+fn classify(file_name: &str) -> Option<(LicenseFileKind, Option<String>)> {
+    let upper = file_name.to_ascii_uppercase();
+    let name = match upper.rsplit_once('.') {
+        Some((stem, extension))
+            if TEXT_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()) =>
+        {
+            stem
+        }
+        _ => upper.as_str(),
+    };
+
+    if name == "UNLICENSE" {
+        return Some((LicenseFileKind::License, Some("Unlicense".to_owned())));
+    }
+    for prefix in ["NOTICE", "COPYRIGHT"] {
+        if name.starts_with(prefix) {
+            return Some((LicenseFileKind::Notice, None));
+        }
+    }
+    for prefix in ["LICENSE", "LICENCE", "COPYING"] {
+        if let Some(rest) = name.strip_prefix(prefix) {
+            let suffix = rest.trim_start_matches(['-', '_', '.']);
+            // e.g. `LICENSES` or `LICENSED`, which are not license files
+            if !rest.is_empty() && suffix.len() == rest.len() {
+                continue;
+            }
+            return Some((LicenseFileKind::License, license_from_suffix(suffix)));
+        }
+    }
+    None
+}
+
+/// Maps the conventional suffixes of license file names to SPDX identifiers
+fn license_from_suffix(suffix: &str) -> Option<String> {
+    let id = match suffix {
+        "MIT" => "MIT",
+        "APACHE" | "APACHE2" | "APACHE-2.0" | "APACHE2.0" | "APACHE-2" => "Apache-2.0",
+        "BSD-2-CLAUSE" | "BSD2" => "BSD-2-Clause",
+        "BSD-3-CLAUSE" | "BSD3" => "BSD-3-Clause",
+        "ZLIB" => "Zlib",
+        "BOOST" | "BSL" | "BSL-1.0" => "BSL-1.0",
+        "MPL" | "MPL2" | "MPL-2.0" => "MPL-2.0",
+        "ISC" => "ISC",
+        "CC0" | "CC0-1.0" => "CC0-1.0",
+        "UNLICENSE" => "Unlicense",
+        _ => return None,
+    };
+    Some(id.to_owned())
+}
+
+/// Compares the declared SPDX expression with the license files found in the package,
+/// returning a description of every mismatch.
+pub fn check_declared_license(declared: Option<&str>, files: &[LicenseFile]) -> Vec<String> {
+    let license_files: Vec<&LicenseFile> = files
+        .iter()
+        .filter(|f| f.kind == LicenseFileKind::License)
+        .collect();
+    let Some(declared) = declared else {
+        return license_files
+            .iter()
+            .map(|f| format!("{} is present, but no license is declared", f.relative_path))
+            .collect();
+    };
+
+    let declared_ids = license_ids(declared);
+    let mut problems = Vec::new();
+    for file in &license_files {
+        if let Some(license) = &file.license {
+            if !declared_ids
+                .iter()
+                .any(|id| id.eq_ignore_ascii_case(license))
+            {
+                problems.push(format!(
+                    "{} suggests {}, which is not in the declared license {}",
+                    file.relative_path, license, declared
+                ));
+            }
+        }
+    }
+    // Only crates shipping a separate file for every license can be checked for missing ones
+    if !license_files.is_empty() && license_files.iter().all(|f| f.license.is_some()) {
+        for id in &declared_ids {
+            let found = license_files.iter().any(|f| {
+                f.license
+                    .as_deref()
+                    .is_some_and(|l| l.eq_ignore_ascii_case(id))
+            });
+            if !found {
+                problems.push(format!(
+                    "{} is declared, but there is no license file for it",
+                    id
+                ));
+            }
+        }
+    }
+    problems
+}
+
+/// Extracts the license identifiers from an SPDX expression, leaving out exceptions
+fn license_ids(expression: &str) -> Vec<&str> {
+    let mut ids = Vec::new();
+    let mut after_with = false;
+    // `/` is not valid in SPDX expressions, but is still commonly used in place of `OR`
+    for token in expression.split(|c: char| c.is_whitespace() || "()/".contains(c)) {
+        match token {
+            "" => continue,
+            "AND" | "OR" | "and" | "or" => after_with = false,
+            "WITH" | "with" => after_with = true,
+            id if !after_with => ids.push(id.trim_end_matches('+')),
+            _ => after_with = false,
+        }
+    }
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn it_should_classify_file_names() {
+        let mit = Some((LicenseFileKind::License, Some("MIT".to_owned())));
+        assert_eq!(classify("LICENSE-MIT"), mit);
+        assert_eq!(classify("license-mit.md"), mit);
+        assert_eq!(
+            classify("LICENSE-APACHE"),
+            Some((LicenseFileKind::License, Some("Apache-2.0".to_owned())))
+        );
+        assert_eq!(
+            classify("LICENSE.txt"),
+            Some((LicenseFileKind::License, None))
+        );
+        assert_eq!(classify("COPYING"), Some((LicenseFileKind::License, None)));
+        assert_eq!(classify("NOTICE"), Some((LicenseFileKind::Notice, None)));
+        assert_eq!(classify("LICENSES"), None);
+        assert_eq!(classify("README.md"), None);
+    }
+
+    #[test]
+    fn it_should_report_mismatches() {
+        let tmp_dir = assert_fs::TempDir::new().unwrap();
+        tmp_dir.child("LICENSE-MIT").write_str("MIT").unwrap();
+        tmp_dir.child("LICENSE-APACHE").write_str("Apache").unwrap();
+        tmp_dir.child("NOTICE").write_str("Copyright").unwrap();
+        tmp_dir.child("src/lib.rs").touch().unwrap();
+        let package_dir = Utf8PathBuf::try_from(tmp_dir.path().to_path_buf()).unwrap();
+
+        let files = find_license_files(&package_dir).unwrap();
+        let paths: Vec<&str> = files.iter().map(|f| f.relative_path.as_str()).collect();
+        assert_eq!(paths, ["LICENSE-APACHE", "LICENSE-MIT", "NOTICE"]);
+
+        assert!(check_declared_license(Some("MIT OR Apache-2.0"), &files).is_empty());
+        assert_eq!(
+            check_declared_license(Some("MIT"), &files),
+            ["LICENSE-APACHE suggests Apache-2.0, which is not in the declared license MIT"]
+        );
+        assert_eq!(
+            check_declared_license(Some("(MIT OR Apache-2.0) AND Zlib"), &files),
+            ["Zlib is declared, but there is no license file for it"]
+        );
+        assert_eq!(
+            check_declared_license(Some("MIT/Apache-2.0 WITH LLVM-exception"), &files),
+            Vec::<String>::new()
+        );
+        assert_eq!(check_declared_license(None, &files).len(), 2);
+
+        tmp_dir.close().unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Locating and reading `Cargo.lock`.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use cargo_lock::Lockfile;
+
+/// Locates the corresponding `Cargo.lock` file given the location of `Cargo.toml`.
+/// Unless the SBOM is generated from `Cargo.lock` alone, this must be run **after** `cargo metadata`,
+/// which will generate the `Cargo.lock` file and make sure it's up to date.
+pub fn locate_cargo_lock(manifest_path: &Path) -> Result<PathBuf, std::io::Error> {
+    let manifest_path = manifest_path.canonicalize()?;
+    let ancestors = manifest_path.as_path().ancestors();
+
+    for path in ancestors {
+        let potential_lockfile = path.join("Cargo.lock");
+        if potential_lockfile.is_file() {
+            return Ok(potential_lockfile);
+        }
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        "Could not find Cargo.lock in any parent directories",
+    ))
+}
+
+/// Reads a `Cargo.lock` file, including ones in the version 4 format.
+///
+/// Version 4 only differs from version 3 in percent-encoding git references in source URLs,
+/// which are decoded when parsing them anyway, so it is read as version 3.
+pub fn read_lockfile(path: &Path) -> Result<Lockfile, cargo_lock::Error> {
+    let contents = std::fs::read_to_string(path)?;
+    Lockfile::from_str(&downgrade_version_4(&contents))
+}
+
+fn downgrade_version_4(contents: &str) -> String {
+    contents
+        .lines()
+        .map(|line| match line.trim_end() {
+            "version = 4" => "version = 3",
+            _ => line,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_read_version_4() {
+        let contents = r#"version = 4
+
+[[package]]
+name = "foo"
+version = "0.1.0"
+source = "git+https://github.com/example/foo?branch=feature%2Fbar#8c5a7e2c24d1fc3b3f3d5e86e8b6f2f6a3c1f6c9"
+"#;
+        let lockfile = Lockfile::from_str(&downgrade_version_4(contents)).unwrap();
+        let source = lockfile.packages[0].source.as_ref().unwrap();
+        assert_eq!(
+            source.git_reference(),
+            Some(&cargo_lock::package::GitReference::Branch(
+                "feature/bar".to_owned()
+            ))
+        );
+    }
+}
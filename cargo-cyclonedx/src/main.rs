@@ -46,8 +46,10 @@
 * SOFTWARE.
 */
 use cargo_cyclonedx::{
+    advisory_db::AdvisoryDatabase,
+    build_messages::BuildArtifacts,
     config::{SbomConfig, Target},
-    generator::SbomGenerator,
+    generator::{GeneratedSbom, SbomGenerator},
 };
 
 use std::{
@@ -70,24 +72,128 @@ fn main() -> anyhow::Result<()> {
     setup_logging(&args)?;
 
     let cli_config = args.as_config()?;
+    let advisory_db = match &args.advisory_db {
+        Some(path) => {
+            log::trace!("Loading the advisory database from {}", path.display());
+            Some(AdvisoryDatabase::load(path)?)
+        }
+        None => None,
+    };
+    let build_artifacts = match &args.build_messages {
+        Some(path) => {
+            log::trace!("Reading the build messages from {}", path.display());
+            Some(BuildArtifacts::load(path)?)
+        }
+        None => None,
+    };
+    let add_vulnerabilities = |bom: &mut GeneratedSbom| {
+        if let Some(database) = &advisory_db {
+            bom.add_vulnerabilities(database);
+        }
+    };
+
+    if let Some(binary_path) = &args.from_binary {
+        log::trace!("SBOM generation from {} started", binary_path.display());
+        let mut bom = SbomGenerator::create_sbom_from_binary(binary_path, &cli_config)?;
+        add_vulnerabilities(&mut bom);
+        bom.write_to_files()?;
+        return Ok(());
+    }
+
     let manifest_path = locate_manifest(&args)?;
     log::debug!("Found the Cargo.toml file at {}", manifest_path.display());
 
+    if args.lockfile_only {
+        log::trace!("SBOM generation from Cargo.lock started");
+        let mut bom = SbomGenerator::create_sbom_from_lockfile(&manifest_path, &cli_config)?;
+        add_vulnerabilities(&mut bom);
+        bom.write_to_files()?;
+        return Ok(());
+    }
+
+    let targets = match &cli_config.target {
+        Some(Target::MultipleTargets(targets)) => targets.clone(),
+        _ => {
+            if args.combine_targets {
+                log::warn!(
+                    "`--combine-targets` has no effect unless multiple targets are specified"
+                );
+            }
+            generate_sboms(
+                &args,
+                &manifest_path,
+                &cli_config,
+                advisory_db.as_ref(),
+                build_artifacts.as_ref(),
+            )?;
+            return Ok(());
+        }
+    };
+
+    // Dependencies are resolved separately for each target, producing a separate set of SBOMs
+    let mut per_target = Vec::with_capacity(targets.len());
+    for target in targets {
+        let config = cli_config.merge(&SbomConfig {
+            target: Some(Target::SingleTarget(target.clone())),
+            ..SbomConfig::empty_config()
+        });
+        let boms = generate_sboms(
+            &args,
+            &manifest_path,
+            &config,
+            advisory_db.as_ref(),
+            build_artifacts.as_ref(),
+        )?;
+        if args.combine_targets {
+            per_target.push((target, boms));
+        }
+    }
+
+    if args.combine_targets {
+        log::trace!("Combining SBOMs for multiple targets");
+        for mut bom in GeneratedSbom::combine_targets(per_target) {
+            add_vulnerabilities(&mut bom);
+            bom.write_to_files()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Generates and writes the SBOMs for the given configuration, returning them for further processing
+fn generate_sboms(
+    args: &Args,
+    manifest_path: &Path,
+    config: &SbomConfig,
+    advisory_db: Option<&AdvisoryDatabase>,
+    build_artifacts: Option<&BuildArtifacts>,
+) -> anyhow::Result<Vec<GeneratedSbom>> {
     log::trace!("Running `cargo metadata` started");
-    let metadata = get_metadata(&args, &manifest_path, &cli_config)?;
+    let metadata = get_metadata(args, manifest_path, config)?;
     log::trace!("Running `cargo metadata` finished");
 
     log::trace!("SBOM generation started");
-    let boms = SbomGenerator::create_sboms(metadata, &cli_config)?;
+    let mut boms = SbomGenerator::create_sboms(metadata, config)?;
+    if let Some(artifacts) = build_artifacts {
+        boms.iter_mut()
+            .for_each(|bom| bom.apply_build_artifacts(artifacts));
+    }
+    if let Some(database) = advisory_db {
+        boms.iter_mut()
+            .for_each(|bom| bom.add_vulnerabilities(database));
+    }
     log::trace!("SBOM generation finished");
 
     log::trace!("SBOM output started");
-    for bom in boms {
-        bom.write_to_files()?;
+    for bom in boms.iter() {
+        bom.clone().write_to_files()?;
+        if let Some(directory) = &args.embed_in_binaries {
+            bom.embed_into_binaries(directory)?;
+        }
     }
     log::trace!("SBOM output finished");
 
-    Ok(())
+    Ok(boms)
 }
 
 fn setup_logging(args: &Args) -> anyhow::Result<()> {
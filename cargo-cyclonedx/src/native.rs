@@ -0,0 +1,194 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Detection of the native libraries that crates link to, as declared with the `links` key of `Cargo.toml`.
+//!
+//! Crates such as `openssl-sys` or `libgit2-sys` link a native library into the final artifact,
+//! either one installed on the system or one built from C sources bundled in the crate
+//! or in a companion `-src` crate such as `openssl-src`.
+//! The native library is described as a separate component, since that is where most of the vulnerabilities are.
+//!
+//! `-sys` crates without `links`, such as `windows-sys`, only provide bindings and are not considered native libraries.
+
+use cargo_metadata::camino::Utf8Path;
+use cargo_metadata::semver::Version;
+use cargo_metadata::Package;
+
+/// The extensions of C and C++ source files
+const NATIVE_SOURCE_EXTENSIONS: &[&str] = &["c", "cc", "cpp", "cxx"];
+
+/// Directories that contain code not linked into the library
+const SKIPPED_DIRECTORIES: &[&str] = &["target", "tests", "test", "examples", "benches", "fuzz"];
+
+/// How deep to look for C sources in the directories of a package
+const MAX_DEPTH: usize = 6;
+
+/// A native library linked by a crate
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NativeLibrary {
+    /// The name from the `links` key, e.g. `git2` for `libgit2-sys`
+    pub name: String,
+    /// The version of the native library, if the crate records it
+    pub version: Option<String>,
+    /// The directories with the bundled C sources, relative to the package root, e.g. `libgit2`
+    pub bundled_sources: Vec<String>,
+}
+
+/// Returns the native library linked by `package`, if it declares one with `links`.
+///
+/// `source_crate` is the `-src` crate the package depends on, if any,
+/// whose version takes precedence since it is the one the library is built from.
+pub fn native_library(package: &Package, source_crate: Option<&Package>) -> Option<NativeLibrary> {
+    let name = package.links.clone()?;
+    let version = source_crate
+        .and_then(|source| upstream_version(&source.version))
+        .or_else(|| upstream_version(&package.version));
+    let bundled_sources = match package.manifest_path.parent() {
+        Some(package_dir) => find_bundled_sources(package_dir),
+        None => Vec::new(),
+    };
+    Some(NativeLibrary {
+        name,
+        version,
+        bundled_sources,
+    })
+}
+
+/// Whether the package only exists to ship the sources of a native library, e.g. `openssl-src`
+pub fn is_source_crate(package: &Package) -> bool {
+    package.name.ends_with("-src")
+}
+
+/// Many native library crates record the version of the library as build metadata,
+/// e.g. `300.2.3+3.2.1` for `openssl-src` or `2.0.9+zstd.1.5.5` for `zstd-sys`
+pub fn upstream_version(version: &Version) -> Option<String> {
+    let metadata = version.build.as_str();
+    let start = metadata
+        .split('.')
+        .position(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))?;
+    let version: Vec<&str> = metadata.split('.').skip(start).collect();
+    Some(version.join("."))
+}
+
+/// Finds the directories with C or C++ sources bundled in a package.
+///
+/// Top-level directories are reported as a whole, e.g. `libgit2` rather than `libgit2/src/libgit2`.
+/// Within `src`, which holds the Rust code, its subdirectories are reported instead, e.g. `src/zlib`.
+/// C files directly in `src` are usually small shims and are not reported.
+pub fn find_bundled_sources(package_dir: &Utf8Path) -> Vec<String> {
+    let mut bundled = Vec::new();
+    for dir in subdirectories(package_dir) {
+        if dir.file_name() == Some("src") {
+            bundled.extend(
+                subdirectories(&dir)
+                    .into_iter()
+                    .filter(|dir| contains_native_sources(dir, MAX_DEPTH)),
+            );
+        } else if contains_native_sources(&dir, MAX_DEPTH) {
+            bundled.push(dir);
+        }
+    }
+
+    let mut bundled: Vec<String> = bundled
+        .iter()
+        .filter_map(|dir| dir.strip_prefix(package_dir).ok())
+        // Paths are recorded with `/` as the separator on all platforms
+        .map(|dir| {
+            dir.components()
+                .map(|c| c.as_str())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect();
+    bundled.sort();
+    bundled
+}
+
+/// Lists the subdirectories that may contain bundled sources, skipping hidden directories,
+/// the ones with tests or examples, and nested packages
+fn subdirectories(dir: &Utf8Path) -> Vec<cargo_metadata::camino::Utf8PathBuf> {
+    let entries = match dir.read_dir_utf8() {
+        Ok(entries) => entries,
+        Err(error) => {
+            log::debug!("Failed to list {}: {}", dir, error);
+            return Vec::new();
+        }
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|entry| {
+            let name = entry.file_name();
+            !name.starts_with('.')
+                && !SKIPPED_DIRECTORIES.contains(&name)
+                && !entry.path().join("Cargo.toml").is_file()
+        })
+        .map(|entry| entry.path().to_owned())
+        .collect()
+}
+
+fn contains_native_sources(dir: &Utf8Path, depth: usize) -> bool {
+    let Ok(entries) = dir.read_dir_utf8() else {
+        return false;
+    };
+    let has_sources = entries.flatten().any(|entry| {
+        let path = entry.path();
+        path.extension().is_some_and(|ext| {
+            NATIVE_SOURCE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str())
+        }) && entry.file_type().is_ok_and(|t| t.is_file())
+    });
+    has_sources
+        || (depth > 0
+            && subdirectories(dir)
+                .iter()
+                .any(|dir| contains_native_sources(dir, depth - 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn upstream_version_from_build_metadata() {
+        let version = |v: &str| upstream_version(&Version::parse(v).unwrap());
+        assert_eq!(version("300.2.3+3.2.1").as_deref(), Some("3.2.1"));
+        assert_eq!(version("2.0.9+zstd.1.5.5").as_deref(), Some("1.5.5"));
+        assert_eq!(version("0.16.2+1.7.2").as_deref(), Some("1.7.2"));
+        assert_eq!(version("1.1.15"), None);
+        assert_eq!(version("1.0.0+git.abcdef"), None);
+    }
+
+    #[test]
+    fn finds_bundled_sources() {
+        let tmp_dir = assert_fs::TempDir::new().unwrap();
+        tmp_dir.child("src/lib.rs").touch().unwrap();
+        tmp_dir.child("src/shim.c").touch().unwrap();
+        tmp_dir.child("src/zlib/deflate.c").touch().unwrap();
+        tmp_dir.child("libgit2/src/util/util.c").touch().unwrap();
+        tmp_dir.child("libgit2/include/git2.h").touch().unwrap();
+        tmp_dir.child("include/foo.h").touch().unwrap();
+        tmp_dir.child("tests/test.c").touch().unwrap();
+        tmp_dir.child(".git/hook.c").touch().unwrap();
+
+        let package_dir = Utf8Path::from_path(tmp_dir.path()).unwrap();
+        assert_eq!(find_bundled_sources(package_dir), ["libgit2", "src/zlib"]);
+
+        tmp_dir.close().unwrap();
+    }
+}
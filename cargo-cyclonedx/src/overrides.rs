@@ -0,0 +1,283 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Detection of packages overridden with `[patch]` and `[replace]`.
+//!
+//! `cargo metadata` reports overridden packages as if they were ordinary ones,
+//! so the overrides are read from the workspace `Cargo.toml` and from `Cargo.lock`.
+
+use std::collections::HashMap;
+
+use cargo_lock::Lockfile;
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
+use cargo_metadata::{Package, PackageId};
+use serde::Deserialize;
+
+use crate::git_source::{normalize_repository_url, GitSource};
+
+/// The name Cargo uses for crates.io in `[patch]` tables
+const CRATES_IO: &str = "crates-io";
+
+/// How a package has been overridden
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverrideKind {
+    Patch,
+    Replace,
+}
+
+/// A package that is used in place of the one that would have been fetched otherwise
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Override {
+    pub kind: OverrideKind,
+    /// Where the original package would have been fetched from
+    pub original_source: OriginalSource,
+    /// The version of the original package, if known.
+    /// `[patch]` does not record which version would have been used without the patch.
+    pub original_version: Option<String>,
+    /// The requirements the packages depending on the original package place on its version,
+    /// e.g. `^1`, for when the version itself is not known
+    pub original_requirement: Option<String>,
+}
+
+/// Where an overridden package would have been fetched from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OriginalSource {
+    CratesIo,
+    /// A registry or git repository URL
+    Url(String),
+    /// A registry configured in `.cargo/config.toml`
+    Registry(String),
+}
+
+impl OriginalSource {
+    fn from_patch_key(key: &str) -> Self {
+        if key == CRATES_IO {
+            OriginalSource::CratesIo
+        } else if key.contains("://") {
+            OriginalSource::Url(key.to_owned())
+        } else {
+            OriginalSource::Registry(key.to_owned())
+        }
+    }
+
+    fn from_lockfile_source(source: &cargo_lock::SourceId) -> Self {
+        if source.is_default_registry() {
+            OriginalSource::CratesIo
+        } else {
+            OriginalSource::Url(source.url().to_string())
+        }
+    }
+
+    /// The notation used for the source in `Cargo.toml`
+    pub fn as_str(&self) -> &str {
+        match self {
+            OriginalSource::CratesIo => CRATES_IO,
+            OriginalSource::Url(url) | OriginalSource::Registry(url) => url,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    patch: HashMap<String, HashMap<String, toml::Value>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PatchSpec {
+    package: Option<String>,
+    path: Option<Utf8PathBuf>,
+    git: Option<String>,
+}
+
+/// Finds the resolved packages that have been overridden with `[patch]` in the workspace `Cargo.toml`
+/// or with `[replace]`, which is recorded in `Cargo.lock`.
+pub fn find_overrides<'a>(
+    workspace_root: &Utf8Path,
+    lockfile: Option<&Lockfile>,
+    packages: impl Iterator<Item = &'a Package> + Clone,
+) -> HashMap<PackageId, Override> {
+    let mut overrides = HashMap::new();
+
+    if let Some(lockfile) = lockfile {
+        for locked in &lockfile.packages {
+            let (Some(_), Some(source)) = (&locked.replace, &locked.source) else {
+                continue;
+            };
+            // `[replace]` requires the replacement to have the same name and version
+            for package in packages.clone().filter(|p| {
+                p.name == locked.name.as_str()
+                    && p.version.to_string() == locked.version.to_string()
+            }) {
+                overrides.insert(
+                    package.id.clone(),
+                    Override {
+                        kind: OverrideKind::Replace,
+                        original_source: OriginalSource::from_lockfile_source(source),
+                        original_version: Some(locked.version.to_string()),
+                        original_requirement: None,
+                    },
+                );
+            }
+        }
+    }
+
+    for (key, patches) in read_patches(workspace_root) {
+        for (name, spec) in patches {
+            // Patches that only specify a version substitute one registry for another,
+            // which `[patch]` does not allow for the same registry
+            let Ok(spec) = spec.try_into::<PatchSpec>() else {
+                continue;
+            };
+            let name = spec.package.as_deref().unwrap_or(&name);
+            for package in packages
+                .clone()
+                .filter(|p| p.name == name && is_patch_source(p, &spec, workspace_root))
+            {
+                overrides.insert(
+                    package.id.clone(),
+                    Override {
+                        kind: OverrideKind::Patch,
+                        original_source: OriginalSource::from_patch_key(&key),
+                        original_version: None,
+                        original_requirement: version_requirement(name, packages.clone()),
+                    },
+                );
+            }
+        }
+    }
+
+    overrides
+}
+
+/// The version requirements of the packages depending on the named package, joined with `, `
+/// since the resolved version has to satisfy all of them
+fn version_requirement<'a>(
+    name: &str,
+    packages: impl Iterator<Item = &'a Package>,
+) -> Option<String> {
+    let mut requirements: Vec<String> = packages
+        .flat_map(|p| p.dependencies.iter())
+        .filter(|dependency| dependency.name == name && dependency.path.is_none())
+        .map(|dependency| dependency.req.to_string())
+        .collect();
+    requirements.sort();
+    requirements.dedup();
+    (!requirements.is_empty()).then(|| requirements.join(", "))
+}
+
+/// Reads the `[patch]` tables from the workspace `Cargo.toml`
+fn read_patches(workspace_root: &Utf8Path) -> HashMap<String, HashMap<String, toml::Value>> {
+    let manifest_path = workspace_root.join("Cargo.toml");
+    let manifest: Manifest = match std::fs::read_to_string(&manifest_path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| toml::from_str(&contents).map_err(|e| e.to_string()))
+    {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            log::warn!(
+                "Failed to read the [patch] section of {}: {}\n\
+                Patched packages will not be marked as modified in the SBOM.",
+                manifest_path,
+                e
+            );
+            Manifest::default()
+        }
+    };
+    manifest.patch
+}
+
+/// Whether the package has been fetched from the location specified in the patch
+fn is_patch_source(package: &Package, spec: &PatchSpec, workspace_root: &Utf8Path) -> bool {
+    if let Some(path) = &spec.path {
+        if package.source.is_some() {
+            return false;
+        }
+        let patch_dir = workspace_root.join(path);
+        let package_dir = package
+            .manifest_path
+            .parent()
+            .expect("manifest_path in `cargo metadata` output is not a file!");
+        // The paths may differ in `..` components or symbolic links
+        return match (patch_dir.canonicalize(), package_dir.canonicalize()) {
+            (Ok(patch_dir), Ok(package_dir)) => patch_dir == package_dir,
+            _ => patch_dir == package_dir,
+        };
+    }
+    if let Some(url) = &spec.git {
+        return GitSource::from_package(package).is_some_and(|git| {
+            normalize_repository_url(&git.repository) == normalize_repository_url(url)
+        });
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    const GIT_PACKAGE_JSON: &str = include_str!("../tests/fixtures/git_package.json");
+
+    #[test]
+    fn git_patch() {
+        let tmp_dir = assert_fs::TempDir::new().unwrap();
+        tmp_dir
+            .child("Cargo.toml")
+            .write_str(
+                r#"
+                [workspace]
+                [patch.crates-io]
+                auditable-extract = { git = "https://github.com/rust-secure-code/cargo-auditable" }
+                other = { path = "other" }
+                "#,
+            )
+            .unwrap();
+        let workspace_root = Utf8PathBuf::try_from(tmp_dir.path().to_path_buf()).unwrap();
+        let package: Package = serde_json::from_str(GIT_PACKAGE_JSON).unwrap();
+
+        let overrides = find_overrides(&workspace_root, None, [&package].into_iter());
+        assert_eq!(
+            overrides[&package.id],
+            Override {
+                kind: OverrideKind::Patch,
+                original_source: OriginalSource::CratesIo,
+                original_version: None,
+                original_requirement: None,
+            }
+        );
+
+        tmp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn original_source() {
+        assert_eq!(
+            OriginalSource::from_patch_key("crates-io"),
+            OriginalSource::CratesIo
+        );
+        assert_eq!(
+            OriginalSource::from_patch_key("https://github.com/example/repo"),
+            OriginalSource::Url("https://github.com/example/repo".to_owned())
+        );
+        assert_eq!(
+            OriginalSource::from_patch_key("my-registry").as_str(),
+            "my-registry"
+        );
+    }
+}
@@ -1,9 +1,19 @@
 use std::{
+    collections::{HashMap, HashSet},
     ffi::{OsStr, OsString},
     io::BufRead,
+    path::{Path, PathBuf},
     process::Command,
+    str::FromStr,
 };
 
+use cargo_platform::{Cfg, Platform};
+use cyclonedx_bom::models::{
+    bom::Bom,
+    component::{Component, Components},
+};
+use thiserror::Error;
+
 /// Returns the host target triple, e.g. `x86_64-unknown-linux-gnu`
 pub fn host_platform() -> String {
     rustc_host_target_triple(&rustc_location())
@@ -15,6 +25,22 @@ pub fn rustc_location() -> OsString {
     std::env::var_os("RUSTC").unwrap_or("rustc".into())
 }
 
+pub fn cargo_location() -> OsString {
+    // Set by Cargo when running `cargo cyclonedx`:
+    // https://doc.rust-lang.org/cargo/reference/environment-variables.html
+    std::env::var_os("CARGO").unwrap_or("cargo".into())
+}
+
+/// Returns the directory Cargo keeps the registry cache and git checkouts in:
+/// `CARGO_HOME` if set, `~/.cargo` otherwise
+pub fn cargo_home() -> Option<PathBuf> {
+    if let Some(home) = std::env::var_os("CARGO_HOME").filter(|home| !home.is_empty()) {
+        return Some(home.into());
+    }
+    let user_home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(Path::new(&user_home).join(".cargo"))
+}
+
 /// Returns the default target triple for the rustc we're running
 pub fn rustc_host_target_triple(rustc_path: &OsStr) -> String {
     // While this feels somewhat insane, this is how `cargo` determines the host platform
@@ -29,3 +55,227 @@ pub fn rustc_host_target_triple(rustc_path: &OsStr) -> String {
         .map(|l| l[6..].to_string())
         .expect("Failed to parse rustc output to determine the current platform. Please report this bug!")
 }
+
+/// Returns the `cfg` values set for the target triple, as printed by `rustc --print cfg`
+pub fn target_cfg(target: &str) -> Result<Vec<Cfg>, TargetCfgError> {
+    let output = Command::new(rustc_location())
+        .args(["--print", "cfg", "--target", target])
+        .output()?;
+    if !output.status.success() {
+        return Err(TargetCfgError::RustcError(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ));
+    }
+
+    let mut cfg = Vec::new();
+    for line in output.stdout.lines() {
+        cfg.push(Cfg::from_str(&line?)?);
+    }
+    Ok(cfg)
+}
+
+#[derive(Error, Debug)]
+pub enum TargetCfgError {
+    #[error("Failed to invoke rustc")]
+    IoError(#[from] std::io::Error),
+
+    #[error("rustc failed to print the cfg values for the target: {}", .0)]
+    RustcError(String),
+
+    #[error("Failed to parse the cfg values printed by rustc")]
+    ParseError(#[from] cargo_platform::ParseError),
+}
+
+/// Narrows down a SBOM generated with `--target all` to the dependencies used on a single target.
+///
+/// `target` is the target triple and `cfg` are its `cfg` values, as returned by [target_cfg].
+/// Dependency relationships whose `cdx:cargo:edge-cfg` predicates do not match the target are removed,
+/// followed by every component that is no longer reachable from the toplevel component.
+pub fn specialize_for_target(bom: &Bom, target: &str, cfg: &[Cfg]) -> Bom {
+    let mut bom = bom.clone();
+
+    // Collect the conditions of every conditional dependency relationship
+    let mut edge_conditions: HashMap<(String, String), Vec<String>> = HashMap::new();
+    for component in bom.components.iter().flat_map(|c| c.0.iter()) {
+        let Some(bom_ref) = &component.bom_ref else {
+            continue;
+        };
+        for property in component.properties.iter().flat_map(|p| p.0.iter()) {
+            if property.name != "cdx:cargo:edge-cfg" {
+                continue;
+            }
+            if let Some((dependent, condition)) = split_edge_condition(&property.value) {
+                edge_conditions
+                    .entry((dependent.to_owned(), bom_ref.clone()))
+                    .or_default()
+                    .push(condition.to_owned());
+            }
+        }
+    }
+
+    // A relationship applies if any of its conditions match.
+    // Keep the ones with unparseable conditions rather than silently dropping dependencies.
+    let applies = |dependent: &str, dependency: &str| match edge_conditions
+        .get(&(dependent.to_owned(), dependency.to_owned()))
+    {
+        Some(conditions) => conditions
+            .iter()
+            .any(|c| Platform::from_str(c).map_or(true, |p| p.matches(target, cfg))),
+        None => true,
+    };
+
+    let Some(dependencies) = bom.dependencies.as_mut() else {
+        return bom;
+    };
+    for dependency in dependencies.0.iter_mut() {
+        let dependent = dependency.dependency_ref.clone();
+        dependency
+            .dependencies
+            .retain(|dependency| applies(&dependent, dependency));
+    }
+
+    // Find the components that are still reachable from the toplevel component
+    let root = bom
+        .metadata
+        .as_ref()
+        .and_then(|m| m.component.as_ref())
+        .and_then(|c| c.bom_ref.clone());
+    let graph: HashMap<&String, &Vec<String>> = dependencies
+        .0
+        .iter()
+        .map(|d| (&d.dependency_ref, &d.dependencies))
+        .collect();
+    let mut reachable: HashSet<String> = HashSet::new();
+    let mut queue: Vec<String> = root.into_iter().collect();
+    while let Some(bom_ref) = queue.pop() {
+        if let Some(dependencies) = graph.get(&bom_ref) {
+            queue.extend(
+                dependencies
+                    .iter()
+                    .filter(|d| !reachable.contains(*d))
+                    .cloned(),
+            );
+        }
+        reachable.insert(bom_ref);
+    }
+
+    dependencies
+        .0
+        .retain(|d| reachable.contains(&d.dependency_ref));
+    if let Some(components) = bom.components.take() {
+        let components: Vec<Component> = components
+            .0
+            .into_iter()
+            .filter(|c| c.bom_ref.as_ref().map_or(true, |r| reachable.contains(r)))
+            .collect();
+        bom.components = Some(Components(components));
+    }
+
+    bom
+}
+
+/// Splits the value of a `cdx:cargo:edge-cfg` property into the `bom-ref` of the dependent component
+/// and the condition. The condition is either a `cfg()` expression or a target triple,
+/// the latter of which never contains spaces.
+fn split_edge_condition(value: &str) -> Option<(&str, &str)> {
+    match value.find(" cfg(") {
+        Some(index) => Some((&value[..index], &value[index + 1..])),
+        None => value.rsplit_once(' '),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cyclonedx_bom::models::{
+        component::Classification,
+        dependency::{Dependencies, Dependency},
+        metadata::Metadata,
+        property::{Properties, Property},
+    };
+
+    fn component(name: &str, edge_cfg: &[&str]) -> Component {
+        let mut component =
+            Component::new(Classification::Library, name, "1.0.0", Some(name.into()));
+        if !edge_cfg.is_empty() {
+            let properties = edge_cfg
+                .iter()
+                .map(|value| Property::new("cdx:cargo:edge-cfg", value))
+                .collect();
+            component.properties = Some(Properties(properties));
+        }
+        component
+    }
+
+    fn dependency(dependent: &str, dependencies: &[&str]) -> Dependency {
+        Dependency {
+            dependency_ref: dependent.to_owned(),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn it_should_split_edge_conditions() {
+        assert_eq!(
+            split_edge_condition(r#"path+file:///my dir#0.1.0 cfg(target_os = "linux")"#),
+            Some(("path+file:///my dir#0.1.0", r#"cfg(target_os = "linux")"#))
+        );
+        assert_eq!(
+            split_edge_condition("path+file:///my dir#0.1.0 x86_64-pc-windows-msvc"),
+            Some(("path+file:///my dir#0.1.0", "x86_64-pc-windows-msvc"))
+        );
+    }
+
+    #[test]
+    fn it_should_query_target_cfg() {
+        let cfg = target_cfg("x86_64-pc-windows-msvc").unwrap();
+        assert!(cfg.contains(&Cfg::from_str("windows").unwrap()));
+        assert!(!cfg.contains(&Cfg::from_str("unix").unwrap()));
+    }
+
+    #[test]
+    fn it_should_specialize_for_target() {
+        let mut metadata = Metadata::new().unwrap();
+        metadata.component = Some(component("root", &[]));
+        let bom = Bom {
+            metadata: Some(metadata),
+            components: Some(Components(vec![
+                component("common", &[]),
+                component("windows", &["root cfg(windows)"]),
+                component("windows-only-dep", &[]),
+                component("unix", &["root cfg(unix)"]),
+            ])),
+            dependencies: Some(Dependencies(vec![
+                dependency("root", &["common", "windows", "unix"]),
+                dependency("common", &[]),
+                dependency("windows", &["windows-only-dep"]),
+                dependency("windows-only-dep", &[]),
+                dependency("unix", &[]),
+            ])),
+            ..Bom::default()
+        };
+        let linux_cfg: Vec<Cfg> = ["unix", r#"target_os="linux""#]
+            .iter()
+            .map(|c| Cfg::from_str(c).unwrap())
+            .collect();
+
+        let specialized = specialize_for_target(&bom, "x86_64-unknown-linux-gnu", &linux_cfg);
+
+        let names: Vec<String> = specialized
+            .components
+            .unwrap()
+            .0
+            .iter()
+            .map(|c| c.name.to_string())
+            .collect();
+        assert_eq!(names, ["common", "unix"]);
+        assert_eq!(
+            specialized.dependencies.unwrap().0,
+            vec![
+                dependency("root", &["common", "unix"]),
+                dependency("common", &[]),
+                dependency("unix", &[]),
+            ]
+        );
+    }
+}
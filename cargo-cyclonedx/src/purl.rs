@@ -12,26 +12,30 @@ pub fn get_purl(
     root_package: &Package,
     workspace_root: &Utf8Path,
     subpath: Option<&Utf8Path>,
+) -> Result<CdxPurl, PackageError> {
+    let root_package_dir = root_package.manifest_path.parent().unwrap();
+    get_purl_relative_to(package, root_package_dir, workspace_root, subpath, false)
+}
+
+/// Same as [get_purl], but local packages within the workspace are located
+/// relative to `root_dir` instead of the directory of the root package.
+/// This allows describing a virtual workspace, which has no root package.
+///
+/// If `always_relative` is set, local packages outside the workspace are located relative to `root_dir` as well,
+/// so that the PURL does not depend on where the project is checked out.
+pub fn get_purl_relative_to(
+    package: &Package,
+    root_dir: &Utf8Path,
+    workspace_root: &Utf8Path,
+    subpath: Option<&Utf8Path>,
+    always_relative: bool,
 ) -> Result<CdxPurl, PackageError> {
     let mut builder = PurlBuilder::new(PackageType::Cargo, &package.name)
         .with_version(package.version.to_string());
 
     if let Some(source) = &package.source {
         if !source.is_crates_io() {
-            match source.repr.split_once('+') {
-                // qualifier names are taken from the spec, which defines these two for all PURL types:
-                // https://github.com/package-url/purl-spec/blob/master/PURL-SPECIFICATION.rst#known-qualifiers-keyvalue-pairs
-                Some(("git", _git_path)) => {
-                    builder = builder.with_qualifier("vcs_url", source_to_vcs_url(source))?
-                }
-                Some(("registry", registry_url)) => {
-                    builder = builder.with_qualifier("repository_url", urlencode(registry_url))?
-                }
-                Some((source, _path)) => log::warn!("Unknown source kind {}", source),
-                None => {
-                    log::warn!("No '+' separator found in source field from `cargo metadata`")
-                }
-            }
+            builder = with_source_qualifier(builder, &source.repr)?;
         }
     } else {
         // source is None for packages from the local filesystem.
@@ -39,10 +43,9 @@ pub fn get_purl(
         let mut package_dir = package.manifest_path.parent().unwrap().to_owned();
         // If the package is within the workspace, encode the relative path instead of the absolute one
         // to make the SBOM reproducible(ish) and more clearly signal first-party dependencies.
-        if package_dir.starts_with(workspace_root) {
-            let root_package_dir = root_package.manifest_path.parent().unwrap();
-            debug_assert!(root_package_dir.starts_with(workspace_root));
-            package_dir = diff_utf8_paths(package_dir, root_package_dir).unwrap();
+        if package_dir.starts_with(workspace_root) || always_relative {
+            debug_assert!(root_dir.starts_with(workspace_root));
+            package_dir = diff_utf8_paths(package_dir, root_dir).unwrap();
             if package_dir.as_str() == "" {
                 // if the diff is empty, we are in the current directory
                 package_dir = ".".into();
@@ -66,11 +69,61 @@ pub fn get_purl(
     Ok(CdxPurl::from_str(&purl.to_string()).unwrap())
 }
 
+/// Returns the PURL of a package listed in `Cargo.lock`.
+///
+/// `Cargo.lock` does not record the location of local packages, so they have no PURL.
+pub fn get_lockfile_purl(package: &cargo_lock::Package) -> Option<Result<CdxPurl, PackageError>> {
+    let source = package.source.as_ref()?;
+    let purl = || {
+        let mut builder = PurlBuilder::new(PackageType::Cargo, package.name.as_str())
+            .with_version(package.version.to_string());
+        if !source.is_default_registry() {
+            builder = with_source_qualifier(builder, &source.to_string())?;
+        }
+        let purl = builder.build()?;
+        Ok(CdxPurl::from_str(&purl.to_string()).unwrap())
+    };
+    Some(purl())
+}
+
+/// Records a source other than crates.io, in the format used by `cargo metadata` and `Cargo.lock`
+fn with_source_qualifier(builder: PurlBuilder, source: &str) -> Result<PurlBuilder, PackageError> {
+    match source.split_once('+') {
+        // qualifier names are taken from the spec, which defines these two for all PURL types:
+        // https://github.com/package-url/purl-spec/blob/master/PURL-SPECIFICATION.rst#known-qualifiers-keyvalue-pairs
+        Some(("git", _git_path)) => {
+            Ok(builder.with_qualifier("vcs_url", source_to_vcs_url(source))?)
+        }
+        Some(("registry", registry_url)) => {
+            Ok(builder.with_qualifier("repository_url", urlencode(registry_url))?)
+        }
+        Some((source, _path)) => {
+            log::warn!("Unknown source kind {}", source);
+            Ok(builder)
+        }
+        None => {
+            log::warn!("No '+' separator found in source field from `cargo metadata`");
+            Ok(builder)
+        }
+    }
+}
+
+/// Returns the PURL of the crate with the given name and version published to crates.io,
+/// or of any version of the crate if the version is not known
+pub fn get_crates_io_purl(name: &str, version: Option<&str>) -> Result<CdxPurl, PackageError> {
+    let mut builder = PurlBuilder::new(PackageType::Cargo, name);
+    if let Some(version) = version {
+        builder = builder.with_version(version);
+    }
+    let purl = builder.build()?;
+    Ok(CdxPurl::from_str(&purl.to_string()).unwrap())
+}
+
 /// Converts the `cargo metadata`'s `source` field to a valid PURL `vcs_url`.
 /// Assumes that the source kind is `git`, panics if it isn't.
-fn source_to_vcs_url(source: &cargo_metadata::Source) -> String {
-    assert!(source.repr.starts_with("git+"));
-    urlencode(&source.repr.replace('#', "@"))
+fn source_to_vcs_url(source: &str) -> String {
+    assert!(source.starts_with("git+"));
+    urlencode(&source.replace('#', "@"))
 }
 
 /// Converts a relative path to PURL subpath
@@ -107,7 +160,7 @@ mod tests {
         )
         .unwrap();
         // Validate that data roundtripped correctly
-        let parsed_purl = Purl::from_str(&purl.to_string()).unwrap();
+        let parsed_purl = Purl::from_str(purl.as_ref()).unwrap();
         assert_eq!(parsed_purl.name(), "aho-corasick");
         assert_eq!(parsed_purl.version(), Some("1.1.2"));
         assert!(parsed_purl.qualifiers().is_empty());
@@ -120,7 +173,7 @@ mod tests {
         let git_package: Package = serde_json::from_str(GIT_PACKAGE_JSON).unwrap();
         let purl = get_purl(&git_package, &git_package, Utf8Path::new("/foo/bar"), None).unwrap();
         // Validate that data roundtripped correctly
-        let parsed_purl = Purl::from_str(&purl.to_string()).unwrap();
+        let parsed_purl = Purl::from_str(purl.as_ref()).unwrap();
         assert_eq!(parsed_purl.name(), "auditable-extract");
         assert_eq!(parsed_purl.version(), Some("0.3.2"));
         assert_eq!(parsed_purl.qualifiers().len(), 1);
@@ -142,7 +195,7 @@ mod tests {
         )
         .unwrap();
         // Validate that data roundtripped correctly
-        let parsed_purl = Purl::from_str(&purl.to_string()).unwrap();
+        let parsed_purl = Purl::from_str(purl.as_ref()).unwrap();
         assert_eq!(parsed_purl.name(), "cargo-cyclonedx");
         assert_eq!(parsed_purl.version(), Some("0.3.8"));
         assert_eq!(parsed_purl.qualifiers().len(), 1);
@@ -165,7 +218,7 @@ mod tests {
         )
         .unwrap();
         // Validate that data roundtripped correctly
-        let parsed_purl = Purl::from_str(&purl.to_string()).unwrap();
+        let parsed_purl = Purl::from_str(purl.as_ref()).unwrap();
         assert_eq!(parsed_purl.name(), "cargo-cyclonedx");
         assert_eq!(parsed_purl.version(), Some("0.3.8"));
         assert_eq!(parsed_purl.qualifiers().len(), 1);
@@ -189,7 +242,7 @@ mod tests {
         )
         .unwrap();
         // Validate that data roundtripped correctly
-        let parsed_purl = Purl::from_str(&purl.to_string()).unwrap();
+        let parsed_purl = Purl::from_str(purl.as_ref()).unwrap();
         assert_eq!(parsed_purl.name(), "cyclonedx-bom");
         assert_eq!(parsed_purl.version(), Some("0.4.1"));
         assert_eq!(parsed_purl.qualifiers().len(), 1);
@@ -213,7 +266,7 @@ mod tests {
         )
         .unwrap();
         // Validate that data roundtripped correctly
-        let parsed_purl = Purl::from_str(&purl.to_string()).unwrap();
+        let parsed_purl = Purl::from_str(purl.as_ref()).unwrap();
         assert_eq!(parsed_purl.name(), "cyclonedx-bom");
         assert_eq!(parsed_purl.version(), Some("0.4.1"));
         assert_eq!(parsed_purl.qualifiers().len(), 1);
@@ -227,4 +280,18 @@ mod tests {
         assert!(parsed_purl.subpath().is_none());
         assert!(parsed_purl.namespace().is_none());
     }
+
+    #[test]
+    fn local_package_always_relative() {
+        let root_package: Package = serde_json::from_str(ROOT_PACKAGE_JSON).unwrap();
+        let workspace_package: Package = serde_json::from_str(WORKSPACE_PACKAGE_JSON).unwrap();
+        let root_dir = root_package.manifest_path.parent().unwrap();
+        let purl =
+            get_purl_relative_to(&workspace_package, root_dir, root_dir, None, true).unwrap();
+        let parsed_purl = Purl::from_str(purl.as_ref()).unwrap();
+        let (qualifier, value) = parsed_purl.qualifiers().iter().next().unwrap();
+        assert_eq!(qualifier.as_str(), "download_url");
+        let decoded_path = percent_decode(value.as_bytes()).decode_utf8().unwrap();
+        assert_eq!(decoded_path, "file://../cyclonedx-bom");
+    }
 }
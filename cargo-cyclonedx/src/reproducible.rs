@@ -0,0 +1,322 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Normalization of the SBOMs written with `--reproducible`,
+//! so that the output only depends on the project and not on when or where it was generated.
+//!
+//! See <https://reproducible-builds.org/> for the conventions followed here.
+
+use std::path::Path;
+
+use cyclonedx_bom::external_models::date_time::DateTime;
+use cyclonedx_bom::models::bom::{Bom, BomReference, UrnUuid};
+use cyclonedx_bom::models::component::{Component, Components};
+use cyclonedx_bom::models::formulation::workflow::input::RequiredInputField;
+use cyclonedx_bom::models::formulation::workflow::EnvironmentVar;
+use cyclonedx_bom::models::formulation::Formula;
+use cyclonedx_bom::models::property::Properties;
+use once_cell::sync::Lazy;
+use pathdiff::diff_paths;
+use regex::{Captures, Regex};
+use thiserror::Error;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// The environment variable holding the timestamp to record, in seconds since the Unix epoch
+pub const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+/// The UUIDv5 namespace of the serial numbers derived from the contents of a SBOM.
+/// Changing it changes the serial number of every reproducible SBOM.
+const SERIAL_NUMBER_NAMESPACE: Uuid = Uuid::from_u128(0x356f308f_8e97_428a_b765_bc1e72f855d4);
+
+/// Matches `file://` URLs pointing to an absolute path,
+/// such as the ones in the package IDs of local packages
+static FILE_URL: Lazy<Regex> = Lazy::new(|| Regex::new(r"file://(/[^\s#?]*)").unwrap());
+
+/// Matches absolute paths in command-line flags, which are separated by spaces,
+/// or by the `0x1f` character in `CARGO_ENCODED_RUSTFLAGS`
+static ABSOLUTE_PATH: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(^|[\s=,\x1f])(/[^\s=,\x1f]*)").unwrap());
+
+#[derive(Error, Debug)]
+pub enum ReproducibleError {
+    #[error("Invalid {SOURCE_DATE_EPOCH} value {0:?}: expected the number of seconds since the Unix epoch")]
+    InvalidSourceDateEpoch(String),
+}
+
+/// Returns the timestamp set through `SOURCE_DATE_EPOCH`, or `None` if the variable is unset or empty
+pub fn source_date_epoch() -> Result<Option<DateTime>, ReproducibleError> {
+    match std::env::var(SOURCE_DATE_EPOCH) {
+        Ok(value) if !value.trim().is_empty() => parse_source_date_epoch(&value).map(Some),
+        _ => Ok(None),
+    }
+}
+
+fn parse_source_date_epoch(value: &str) -> Result<DateTime, ReproducibleError> {
+    let invalid = || ReproducibleError::InvalidSourceDateEpoch(value.to_owned());
+    let seconds: i64 = value.trim().parse().map_err(|_| invalid())?;
+    let timestamp = OffsetDateTime::from_unix_timestamp(seconds)
+        .map_err(|_| invalid())?
+        .format(&Rfc3339)
+        .map_err(|_| invalid())?;
+    DateTime::try_from(timestamp).map_err(|_| invalid())
+}
+
+/// Replaces the timestamp with the one from `SOURCE_DATE_EPOCH`, or removes it if that is unset,
+/// records local paths relative to `base_dir` and sorts every list whose order carries no meaning.
+///
+/// The serial number is removed; it should be set to [content_serial_number] after the SBOM is complete.
+pub fn normalize(bom: &mut Bom, base_dir: &Path) -> Result<(), ReproducibleError> {
+    let timestamp = source_date_epoch()?;
+    bom.serial_number = None;
+
+    if let Some(metadata) = &mut bom.metadata {
+        metadata.timestamp = timestamp;
+        if let Some(component) = &mut metadata.component {
+            normalize_component(component, base_dir);
+        }
+        if let Some(properties) = &mut metadata.properties {
+            normalize_properties(properties, base_dir);
+        }
+    }
+
+    if let Some(components) = &mut bom.components {
+        normalize_components(components, base_dir);
+    }
+
+    if let Some(dependencies) = &mut bom.dependencies {
+        for dependency in dependencies.0.iter_mut() {
+            dependency.dependency_ref = relativize_paths(&dependency.dependency_ref, base_dir);
+            for dependency_ref in dependency.dependencies.iter_mut() {
+                *dependency_ref = relativize_paths(dependency_ref, base_dir);
+            }
+            dependency.dependencies.sort();
+        }
+        dependencies
+            .0
+            .sort_by(|a, b| a.dependency_ref.cmp(&b.dependency_ref));
+    }
+
+    if let Some(compositions) = &mut bom.compositions {
+        for composition in compositions.0.iter_mut() {
+            for references in [
+                &mut composition.assemblies,
+                &mut composition.dependencies,
+                &mut composition.vulnerabilities,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                for reference in references.iter_mut() {
+                    *reference = BomReference::new(relativize_paths(reference.as_ref(), base_dir));
+                }
+                references.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+            }
+        }
+    }
+
+    if let Some(vulnerabilities) = &mut bom.vulnerabilities {
+        for vulnerability in vulnerabilities.0.iter_mut() {
+            if let Some(targets) = &mut vulnerability.vulnerability_targets {
+                for target in targets.0.iter_mut() {
+                    target.bom_ref = relativize_paths(&target.bom_ref, base_dir);
+                }
+                targets.0.sort_by(|a, b| a.bom_ref.cmp(&b.bom_ref));
+            }
+        }
+        vulnerabilities.0.sort_by(|a, b| {
+            let id = |v: &cyclonedx_bom::models::vulnerability::Vulnerability| {
+                v.id.as_ref().map(|id| id.to_string())
+            };
+            id(a).cmp(&id(b))
+        });
+    }
+
+    if let Some(properties) = &mut bom.properties {
+        normalize_properties(properties, base_dir);
+    }
+
+    for formula in bom.formulation.iter_mut().flatten() {
+        normalize_formula(formula, base_dir);
+    }
+
+    Ok(())
+}
+
+/// Derives the serial number from the canonical serialization of a SBOM without a serial number,
+/// so that identical SBOMs get identical serial numbers and different ones get different serial numbers
+pub fn content_serial_number(canonical: &[u8]) -> UrnUuid {
+    Uuid::new_v5(&SERIAL_NUMBER_NAMESPACE, canonical).into()
+}
+
+fn normalize_components(components: &mut Components, base_dir: &Path) {
+    for component in components.0.iter_mut() {
+        normalize_component(component, base_dir);
+    }
+    components.0.sort_by(|a, b| {
+        a.bom_ref
+            .cmp(&b.bom_ref)
+            .then_with(|| (*a.name).cmp(&*b.name))
+            .then_with(|| {
+                let version = |c: &Component| c.version.as_ref().map(|v| v.to_string());
+                version(a).cmp(&version(b))
+            })
+    });
+}
+
+fn normalize_component(component: &mut Component, base_dir: &Path) {
+    if let Some(bom_ref) = &mut component.bom_ref {
+        *bom_ref = relativize_paths(bom_ref, base_dir);
+    }
+    if let Some(properties) = &mut component.properties {
+        normalize_properties(properties, base_dir);
+    }
+    if let Some(components) = &mut component.components {
+        normalize_components(components, base_dir);
+    }
+}
+
+/// Properties such as `cdx:cargo:edge-targets` may contain `bom-ref`s, so their values are rewritten too
+fn normalize_properties(properties: &mut Properties, base_dir: &Path) {
+    for property in properties.0.iter_mut() {
+        let value = relativize_paths(property.value.as_ref(), base_dir);
+        property.value = value.as_str().into();
+    }
+    properties
+        .0
+        .sort_by(|a, b| a.name.cmp(&b.name).then_with(|| (*a.value).cmp(&*b.value)));
+}
+
+/// The environment variables of the build may contain paths of the machine the SBOM is generated on,
+/// e.g. `-L /home/user/lib` in `RUSTFLAGS`
+fn normalize_formula(formula: &mut Formula, base_dir: &Path) {
+    for workflow in formula.workflows.iter_mut().flatten() {
+        for input in workflow.inputs.iter_mut().flatten() {
+            if let RequiredInputField::EnvironmentVars(variables) = &mut input.required {
+                for variable in variables.iter_mut() {
+                    if let EnvironmentVar::Property { value, .. } = variable {
+                        *value = relativize_absolute_paths(value, base_dir);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Rewrites the absolute paths under `base_dir` in a list of command-line flags to relative ones.
+/// Paths start at the beginning of a flag or after `=` or `,`, e.g. in `--remap-path-prefix=/src=.`
+///
+/// Paths outside of `base_dir` are left as they are: they do not depend on where the project is checked out,
+/// and rewriting them would record flags that were never passed.
+fn relativize_absolute_paths(text: &str, base_dir: &Path) -> String {
+    ABSOLUTE_PATH
+        .replace_all(text, |captures: &Captures| {
+            match Path::new(&captures[2]).strip_prefix(base_dir) {
+                Ok(path) if path.as_os_str().is_empty() => format!("{}.", &captures[1]),
+                Ok(path) => format!("{}{}", &captures[1], path.display()),
+                Err(_) => captures[0].to_owned(),
+            }
+        })
+        .into_owned()
+}
+
+/// Rewrites every `file://` URL with an absolute path to one relative to `base_dir`,
+/// following the convention of the PURLs of local packages: `file://.` is `base_dir` itself.
+fn relativize_paths(text: &str, base_dir: &Path) -> String {
+    FILE_URL
+        .replace_all(text, |captures: &Captures| {
+            match diff_paths(Path::new(&captures[1]), base_dir) {
+                Some(path) if path.as_os_str().is_empty() => "file://.".to_owned(),
+                Some(path) => format!("file://{}", path.display()),
+                None => captures[0].to_owned(),
+            }
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relativizes_package_ids() {
+        let base_dir = Path::new("/home/user/project");
+        assert_eq!(
+            relativize_paths("path+file:///home/user/project#0.1.0", base_dir),
+            "path+file://.#0.1.0"
+        );
+        assert_eq!(
+            relativize_paths(
+                "path+file:///home/user/project/dep#0.1.0 bin-target-0",
+                base_dir
+            ),
+            "path+file://dep#0.1.0 bin-target-0"
+        );
+        assert_eq!(
+            relativize_paths("path+file:///home/user/sibling#sib@0.1.0", base_dir),
+            "path+file://../sibling#sib@0.1.0"
+        );
+        assert_eq!(
+            relativize_paths(
+                "registry+https://github.com/rust-lang/crates.io-index#log@0.4.20",
+                base_dir
+            ),
+            "registry+https://github.com/rust-lang/crates.io-index#log@0.4.20"
+        );
+    }
+
+    #[test]
+    fn relativizes_paths_in_flags() {
+        let base_dir = Path::new("/home/user/project");
+        assert_eq!(
+            relativize_absolute_paths(
+                "-L /home/user/project/lib --remap-path-prefix=/home/user=~ -C link-arg=-Wl,/opt/x.o",
+                base_dir
+            ),
+            "-L lib --remap-path-prefix=/home/user=~ -C link-arg=-Wl,/opt/x.o"
+        );
+        assert_eq!(
+            relativize_absolute_paths("--remap-path-prefix=/home/user/project=/src", base_dir),
+            "--remap-path-prefix=.=/src"
+        );
+        assert_eq!(
+            relativize_absolute_paths("-L /home/user/project-old/lib", base_dir),
+            "-L /home/user/project-old/lib"
+        );
+        assert_eq!(
+            relativize_absolute_paths("-C\x1fopt-level=3", base_dir),
+            "-C\x1fopt-level=3"
+        );
+    }
+
+    #[test]
+    fn parses_source_date_epoch() {
+        assert_eq!(
+            parse_source_date_epoch("1700000000").unwrap().to_string(),
+            "2023-11-14T22:13:20Z"
+        );
+        assert!(parse_source_date_epoch("yesterday").is_err());
+    }
+
+    #[test]
+    fn serial_number_depends_on_content() {
+        assert_eq!(content_serial_number(b"{}"), content_serial_number(b"{}"));
+        assert_ne!(content_serial_number(b"{}"), content_serial_number(b"[]"));
+    }
+}
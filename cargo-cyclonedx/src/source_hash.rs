@@ -0,0 +1,208 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Hashes the sources of packages that do not come from a registry,
+//! and therefore have no checksum recorded in `Cargo.lock`.
+
+use std::io::BufRead;
+use std::process::Command;
+
+use cargo_metadata::camino::Utf8Path;
+use cyclonedx_bom::models::hash::{Hash, HashAlgorithm, HashValue};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::platform::cargo_location;
+
+/// Files listed by `cargo package --list` that are generated by Cargo
+/// rather than being part of the package sources
+const GENERATED_FILES: &[&str] = &["Cargo.lock", "Cargo.toml.orig", ".cargo_vcs_info.json"];
+
+/// Extensions of the SBOMs written next to `Cargo.toml`, which would otherwise
+/// change the hash of the package every time a SBOM is generated
+const SBOM_EXTENSIONS: &[&str] = &[".cdx.json", ".cdx.xml"];
+
+/// Formats of the SBOMs written with `--override-filename`, which do not have the `.cdx` extension
+const OVERRIDDEN_SBOM_EXTENSIONS: &[&str] = &["json", "xml"];
+
+/// Computes a deterministic SHA-256 hash of the package sources.
+///
+/// The files are the ones `cargo package` would include, minus the ones Cargo generates
+/// and the SBOMs written by previous runs. `filename_override` is the `--override-filename`
+/// the SBOMs are written with, if any.
+/// The hash is computed over the sorted list of `<path>\0<SHA-256 of the file contents>\n` lines,
+/// where `<path>` is relative to the package root and uses `/` as the separator.
+/// This way the hash does not depend on file timestamps or on the archive format.
+pub fn package_source_hash(
+    manifest_path: &Utf8Path,
+    target_directory: &Utf8Path,
+    filename_override: Option<&str>,
+) -> Result<Hash, SourceHashError> {
+    let package_dir = manifest_path
+        .parent()
+        .expect("manifest_path in `cargo metadata` output is not a file!");
+
+    let mut hasher = Sha256::new();
+    for file in package_files(manifest_path, target_directory)?
+        .into_iter()
+        .filter(|file| !is_sbom(file, filename_override))
+    {
+        let path = package_dir.join(&file);
+        let contents = std::fs::read(&path).map_err(|e| SourceHashError::ReadError {
+            path: path.to_string(),
+            source: e,
+        })?;
+        hasher.update(file.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(format!("{:x}", Sha256::digest(&contents)).as_bytes());
+        hasher.update(b"\n");
+    }
+
+    Ok(Hash {
+        alg: HashAlgorithm::SHA_256,
+        content: HashValue(format!("{:x}", hasher.finalize())),
+    })
+}
+
+/// Lists the files that `cargo package` would include, sorted by path
+fn package_files(
+    manifest_path: &Utf8Path,
+    target_directory: &Utf8Path,
+) -> Result<Vec<String>, SourceHashError> {
+    let output = Command::new(cargo_location())
+        .arg("package")
+        .arg("--list")
+        .arg("--allow-dirty")
+        // Keep the packaging artifacts out of the source tree, which may be a git checkout
+        .arg("--target-dir")
+        .arg(target_directory.join("cyclonedx-package"))
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .output()
+        .map_err(SourceHashError::CargoInvocationError)?;
+    if !output.status.success() {
+        return Err(SourceHashError::CargoPackageError(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ));
+    }
+
+    let mut files = Vec::new();
+    for line in output.stdout.lines() {
+        let line = line.map_err(SourceHashError::CargoInvocationError)?;
+        // `cargo package --list` uses `/` as the separator on all platforms
+        if !line.is_empty() && !GENERATED_FILES.contains(&line.as_str()) {
+            files.push(line);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Whether the file is a SBOM written next to `Cargo.toml`, either with the default name,
+/// e.g. `pkg.cdx.json`, or with the overridden one, e.g. `bom.json` or `bom_x86_64-unknown-linux-gnu.cdx.json`
+fn is_sbom(file: &str, filename_override: Option<&str>) -> bool {
+    if SBOM_EXTENSIONS.iter().any(|ext| file.ends_with(ext)) {
+        return true;
+    }
+    let Some(filename_override) = filename_override else {
+        return false;
+    };
+    // SBOMs are only written to the package root
+    let Some((stem, extension)) = file.rsplit_once('.') else {
+        return false;
+    };
+    !file.contains('/')
+        && OVERRIDDEN_SBOM_EXTENSIONS.contains(&extension)
+        && (stem == filename_override
+            || stem
+                .strip_prefix(filename_override)
+                .is_some_and(|suffix| suffix.starts_with('_')))
+}
+
+#[derive(Error, Debug)]
+pub enum SourceHashError {
+    #[error("Failed to invoke cargo")]
+    CargoInvocationError(#[source] std::io::Error),
+
+    #[error("`cargo package --list` failed: {}", .0)]
+    CargoPackageError(String),
+
+    #[error("Failed to read {path}")]
+    ReadError {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+    use cargo_metadata::camino::Utf8PathBuf;
+
+    #[test]
+    fn it_should_hash_package_sources() {
+        let tmp_dir = assert_fs::TempDir::new().unwrap();
+        tmp_dir
+            .child("Cargo.toml")
+            .write_str(r#"package = { name = "pkg", version = "0.0.0" }"#)
+            .unwrap();
+        tmp_dir.child("src/lib.rs").write_str("").unwrap();
+        let manifest_path =
+            Utf8PathBuf::try_from(tmp_dir.child("Cargo.toml").to_path_buf()).unwrap();
+        let target_dir = Utf8PathBuf::try_from(tmp_dir.child("target").to_path_buf()).unwrap();
+
+        let hash = package_source_hash(&manifest_path, &target_dir, None).unwrap();
+        assert_eq!(hash.alg, HashAlgorithm::SHA_256);
+        assert_eq!(
+            hash,
+            package_source_hash(&manifest_path, &target_dir, None).unwrap()
+        );
+
+        tmp_dir.child("pkg.cdx.json").write_str("{}").unwrap();
+        assert_eq!(
+            hash,
+            package_source_hash(&manifest_path, &target_dir, None).unwrap()
+        );
+
+        tmp_dir
+            .child("src/lib.rs")
+            .write_str("pub fn f() {}")
+            .unwrap();
+        assert_ne!(
+            hash,
+            package_source_hash(&manifest_path, &target_dir, None).unwrap()
+        );
+
+        tmp_dir.close().unwrap();
+    }
+
+    #[test]
+    fn it_should_recognize_sboms() {
+        assert!(is_sbom("pkg.cdx.json", None));
+        assert!(!is_sbom("bom.json", None));
+        assert!(is_sbom("bom.json", Some("bom")));
+        assert!(is_sbom("bom.xml", Some("bom")));
+        assert!(is_sbom("bom_bin.json", Some("bom")));
+        assert!(is_sbom("bom_x86_64-unknown-linux-gnu.cdx.xml", Some("bom")));
+        assert!(!is_sbom("bomb.json", Some("bom")));
+        assert!(!is_sbom("src/bom.json", Some("bom")));
+        assert!(!is_sbom("bom.rs", Some("bom")));
+    }
+}
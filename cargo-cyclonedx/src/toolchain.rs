@@ -0,0 +1,583 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Describes the Rust toolchain and the build configuration as a CycloneDX 1.5 formula,
+//! so that the build producing the artifacts can be reproduced.
+//!
+//! The formula lists `rustc`, `cargo` and the standard library as components,
+//! and a `cargo build` workflow recording the profile, target, features
+//! and the environment variables that affect the build.
+
+use std::ffi::OsStr;
+use std::io::BufRead;
+use std::process::Command as ProcessCommand;
+
+use cargo_metadata::camino::Utf8Path;
+use cyclonedx_bom::external_models::normalized_string::NormalizedString;
+use cyclonedx_bom::external_models::uri::Uri;
+use cyclonedx_bom::models::bom::BomReference;
+use cyclonedx_bom::models::component::{Classification, Component, Components};
+use cyclonedx_bom::models::external_reference::{
+    ExternalReference, ExternalReferenceType, ExternalReferences,
+};
+use cyclonedx_bom::models::formulation::workflow::input::{Input, RequiredInputField};
+use cyclonedx_bom::models::formulation::workflow::step::{Command, Step};
+use cyclonedx_bom::models::formulation::workflow::{EnvironmentVar, TaskType, Workflow};
+use cyclonedx_bom::models::formulation::Formula;
+use cyclonedx_bom::models::property::{Properties, Property};
+use thiserror::Error;
+
+use crate::config::SbomConfig;
+use crate::platform::{cargo_location, rustc_location};
+
+/// Environment variables that change the compiled artifacts, besides the ones matched by
+/// [PROFILE_ENV_SETTINGS] and the `CARGO_TARGET_<triple>_RUSTFLAGS` variables:
+/// https://doc.rust-lang.org/cargo/reference/config.html#environment-variables
+///
+/// Variables that only affect how the build runs, such as `CARGO_BUILD_JOBS`, and those naming
+/// host programs, such as `CARGO_BUILD_RUSTC_WRAPPER` or linkers, are deliberately not listed.
+const BUILD_ENV_VARS: &[&str] = &[
+    "RUSTFLAGS",
+    "CARGO_ENCODED_RUSTFLAGS",
+    "CARGO_BUILD_RUSTFLAGS",
+    "CARGO_BUILD_TARGET",
+    "RUSTC_BOOTSTRAP",
+];
+
+/// Profile settings that change the compiled artifacts,
+/// overridden with `CARGO_PROFILE_<name>_<setting>` environment variables
+const PROFILE_ENV_SETTINGS: &[&str] = &[
+    "_OPT_LEVEL",
+    "_DEBUG",
+    "_DEBUG_ASSERTIONS",
+    "_OVERFLOW_CHECKS",
+    "_LTO",
+    "_PANIC",
+    "_CODEGEN_UNITS",
+    "_STRIP",
+    "_RPATH",
+    "_SPLIT_DEBUGINFO",
+];
+
+/// The version of a toolchain binary, as printed by `rustc -vV` or `cargo -vV`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ToolVersion {
+    pub release: String,
+    pub commit_hash: Option<String>,
+    pub commit_date: Option<String>,
+    pub host: Option<String>,
+    pub llvm_version: Option<String>,
+}
+
+impl ToolVersion {
+    /// Runs `<program> -vV` and parses the output
+    pub fn query(program: &OsStr) -> Result<Self, ToolchainError> {
+        let output = ProcessCommand::new(program).arg("-vV").output()?;
+        if !output.status.success() {
+            return Err(ToolchainError::VersionError(
+                String::from_utf8_lossy(&output.stderr).into_owned(),
+            ));
+        }
+        let lines = output.stdout.lines().collect::<Result<Vec<_>, _>>()?;
+        Self::parse(&lines).ok_or_else(|| ToolchainError::VersionError(lines.join("\n")))
+    }
+
+    fn parse(lines: &[String]) -> Option<Self> {
+        let field = |name: &str| {
+            lines.iter().find_map(|line| {
+                let (key, value) = line.split_once(": ")?;
+                // rustc prints "unknown" for toolchains built outside of a git checkout
+                (key == name && value != "unknown").then(|| value.trim().to_owned())
+            })
+        };
+        Some(Self {
+            release: field("release")?,
+            commit_hash: field("commit-hash"),
+            commit_date: field("commit-date"),
+            host: field("host"),
+            llvm_version: field("LLVM version"),
+        })
+    }
+}
+
+/// The `rustc` and `cargo` that build the package
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Toolchain {
+    pub rustc: ToolVersion,
+    pub cargo: ToolVersion,
+}
+
+impl Toolchain {
+    /// Queries the toolchain that Cargo would use, honoring the `RUSTC` and `CARGO` environment variables
+    pub fn detect() -> Result<Self, ToolchainError> {
+        Ok(Self {
+            rustc: ToolVersion::query(&rustc_location())?,
+            cargo: ToolVersion::query(&cargo_location())?,
+        })
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ToolchainError {
+    #[error("Failed to invoke the toolchain")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Failed to determine the toolchain version: {}", .0)]
+    VersionError(String),
+}
+
+/// Describes how the artifacts of a package are built.
+///
+/// `target` is the target triple the package is built for, if known.
+/// `profile_settings` are the settings of the profile from `Cargo.toml`,
+/// and `environment` are the environment variables of the build,
+/// of which only the ones affecting the build are recorded.
+pub fn create_formula(
+    toolchain: &Toolchain,
+    config: &SbomConfig,
+    target: Option<&str>,
+    profile_settings: &[(String, String)],
+    environment: impl IntoIterator<Item = (String, String)>,
+) -> Formula {
+    let rustc = &toolchain.rustc;
+    // Without `--target` Cargo builds for the host
+    let triple = target.or(rustc.host.as_deref());
+    let std_ref = match triple {
+        Some(target) => format!("std@{} {}", rustc.release, target),
+        None => format!("std@{}", rustc.release),
+    };
+    let workflow_ref = match triple {
+        Some(target) => format!("cargo-build {}", target),
+        None => "cargo-build".to_owned(),
+    };
+
+    // The standard library is built for the target rather than the host
+    let std_version = ToolVersion {
+        host: None,
+        llvm_version: None,
+        ..rustc.clone()
+    };
+    let mut std = create_tool_component(
+        Classification::Library,
+        "std",
+        &std_version,
+        std_ref,
+        "The Rust standard library",
+        "https://github.com/rust-lang/rust",
+    );
+    if let Some(triple) = triple {
+        std.properties
+            .get_or_insert(Properties(Vec::new()))
+            .0
+            .push(Property::new("cdx:cargo:target", triple));
+    }
+    let components = vec![
+        create_tool_component(
+            Classification::Application,
+            "rustc",
+            rustc,
+            format!("rustc@{}", rustc.release),
+            "The Rust compiler",
+            "https://github.com/rust-lang/rust",
+        ),
+        std,
+        create_tool_component(
+            Classification::Application,
+            "cargo",
+            &toolchain.cargo,
+            format!("cargo@{}", toolchain.cargo.release),
+            "The Rust package manager",
+            "https://github.com/rust-lang/cargo",
+        ),
+    ];
+
+    let mut properties = Vec::new();
+    if let Some(profile) = config.profile() {
+        properties.push(Property::new("cdx:cargo:profile", profile));
+        for (key, value) in profile_settings {
+            properties.push(Property::new(format!("cdx:cargo:profile:{key}"), value));
+        }
+    }
+    if let Some(triple) = triple {
+        properties.push(Property::new("cdx:cargo:target", triple));
+    }
+    if let Some(features) = &config.features {
+        for feature in &features.features {
+            properties.push(Property::new("cdx:cargo:feature", feature));
+        }
+    }
+
+    let environment: Vec<EnvironmentVar> = build_environment(environment)
+        .into_iter()
+        .map(|(name, value)| EnvironmentVar::Property { name, value })
+        .collect();
+    let inputs = (!environment.is_empty()).then(|| {
+        vec![Input {
+            required: RequiredInputField::EnvironmentVars(environment),
+            source: None,
+            target: None,
+            properties: None,
+        }]
+    });
+
+    let workflow = Workflow {
+        bom_ref: BomReference::new(&workflow_ref),
+        uid: workflow_ref,
+        name: Some("cargo build".to_owned()),
+        description: Some("Builds the artifacts described by the SBOM".to_owned()),
+        resource_references: None,
+        tasks: None,
+        task_dependencies: None,
+        task_types: vec![TaskType::Build],
+        trigger: None,
+        steps: build_command(config, target).map(|command| {
+            vec![Step {
+                commands: Some(vec![Command {
+                    executed: Some(command),
+                    properties: None,
+                }]),
+                description: None,
+                name: Some("cargo build".to_owned()),
+                properties: None,
+            }]
+        }),
+        inputs,
+        outputs: None,
+        time_start: None,
+        time_end: None,
+        workspaces: None,
+        runtime_topology: None,
+        properties: (!properties.is_empty()).then_some(Properties(properties)),
+    };
+
+    Formula {
+        bom_ref: None,
+        components: Some(Components(components)),
+        services: None,
+        workflows: Some(vec![workflow]),
+        properties: None,
+    }
+}
+
+/// Adds the components and workflows of `other` that are not in `formula` yet,
+/// e.g. the standard library and the build of another target
+pub fn merge_formula(formula: &mut Formula, other: &Formula) {
+    let components = formula.components.get_or_insert(Components(Vec::new()));
+    for component in other.components.iter().flat_map(|c| c.0.iter()) {
+        if !components.0.iter().any(|c| c.bom_ref == component.bom_ref) {
+            components.0.push(component.clone());
+        }
+    }
+    let workflows = formula.workflows.get_or_insert(Vec::new());
+    for workflow in other.workflows.iter().flatten() {
+        if !workflows.iter().any(|w| w.bom_ref == workflow.bom_ref) {
+            workflows.push(workflow.clone());
+        }
+    }
+}
+
+/// Reads the settings of a profile from the `[profile]` table of the workspace manifest.
+///
+/// Only the settings that apply to every package are recorded, not the per-package overrides.
+/// The defaults of the built-in profiles are not recorded either.
+pub fn read_profile_settings(workspace_root: &Utf8Path, profile: &str) -> Vec<(String, String)> {
+    let manifest_path = workspace_root.join("Cargo.toml");
+    let manifest = match std::fs::read_to_string(&manifest_path) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            log::warn!("Failed to read {}: {}", manifest_path, e);
+            return Vec::new();
+        }
+    };
+    let manifest: toml::Table = match toml::from_str(&manifest) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            log::warn!("Failed to parse {}: {}", manifest_path, e);
+            return Vec::new();
+        }
+    };
+
+    let Some(settings) = manifest
+        .get("profile")
+        .and_then(|profiles| profiles.get(profile))
+        .and_then(|settings| settings.as_table())
+    else {
+        return Vec::new();
+    };
+    settings
+        .iter()
+        .filter_map(|(key, value)| match value {
+            toml::Value::String(value) => Some((key.clone(), value.clone())),
+            toml::Value::Table(_) | toml::Value::Array(_) => None,
+            value => Some((key.clone(), value.to_string())),
+        })
+        .collect()
+}
+
+/// Keeps the environment variables that change the compiled artifacts, sorted by name.
+///
+/// Their values may still contain paths, e.g. `-L` flags in `RUSTFLAGS`,
+/// which `--reproducible` records relative to the project.
+fn build_environment(
+    environment: impl IntoIterator<Item = (String, String)>,
+) -> Vec<(String, String)> {
+    let mut variables: Vec<(String, String)> = environment
+        .into_iter()
+        .filter(|(name, _)| affects_artifacts(name))
+        .collect();
+    variables.sort();
+    variables
+}
+
+fn affects_artifacts(name: &str) -> bool {
+    BUILD_ENV_VARS.contains(&name)
+        || (name.starts_with("CARGO_PROFILE_")
+            && PROFILE_ENV_SETTINGS
+                .iter()
+                .any(|setting| name.ends_with(setting)))
+        || (name.starts_with("CARGO_TARGET_") && name.ends_with("_RUSTFLAGS"))
+}
+
+/// The `cargo build` invocation that builds the artifacts with the configured profile, target and features.
+/// `None` if no profile is configured, since the command line is not known then.
+fn build_command(config: &SbomConfig, target: Option<&str>) -> Option<String> {
+    let mut command = vec!["cargo".to_owned(), "build".to_owned()];
+    match config.profile()? {
+        "dev" => (),
+        "release" => command.push("--release".to_owned()),
+        profile => command.extend(["--profile".to_owned(), profile.to_owned()]),
+    }
+    if let Some(target) = target {
+        command.extend(["--target".to_owned(), target.to_owned()]);
+    }
+    if let Some(features) = &config.features {
+        if features.all_features {
+            command.push("--all-features".to_owned());
+        }
+        if features.no_default_features {
+            command.push("--no-default-features".to_owned());
+        }
+        if !features.features.is_empty() {
+            command.extend(["--features".to_owned(), features.features.join(",")]);
+        }
+    }
+    Some(command.join(" "))
+}
+
+fn create_tool_component(
+    classification: Classification,
+    name: &str,
+    version: &ToolVersion,
+    bom_ref: String,
+    description: &str,
+    repository: &str,
+) -> Component {
+    let mut component = Component::new(classification, name, &version.release, Some(bom_ref));
+    component.description = Some(NormalizedString::new(description));
+
+    let mut properties = Vec::new();
+    if let Some(commit_date) = &version.commit_date {
+        properties.push(Property::new("cdx:rust:commit-date", commit_date));
+    }
+    if let Some(host) = &version.host {
+        properties.push(Property::new("cdx:rust:host", host));
+    }
+    if let Some(llvm_version) = &version.llvm_version {
+        properties.push(Property::new("cdx:rust:llvm-version", llvm_version));
+    }
+    if !properties.is_empty() {
+        component.properties = Some(Properties(properties));
+    }
+
+    if let Some(commit_hash) = &version.commit_hash {
+        if let Ok(uri) = Uri::try_from(format!("{repository}/commit/{commit_hash}")) {
+            let mut reference = ExternalReference::new(ExternalReferenceType::Vcs, uri);
+            reference.comment = Some("The commit the toolchain was built from".to_owned());
+            component.external_references = Some(ExternalReferences(vec![reference]));
+        }
+    }
+
+    component
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Features;
+
+    fn lines(output: &str) -> Vec<String> {
+        output.lines().map(str::to_owned).collect()
+    }
+
+    fn toolchain() -> Toolchain {
+        let rustc = ToolVersion::parse(&lines(
+            "rustc 1.75.0 (82e1608df 2023-12-21)
+binary: rustc
+commit-hash: 82e1608dfa6e0b5569232559e3d385fea5a93112
+commit-date: 2023-12-21
+host: x86_64-unknown-linux-gnu
+release: 1.75.0
+LLVM version: 17.0.6",
+        ))
+        .unwrap();
+        let cargo = ToolVersion::parse(&lines(
+            "cargo 1.75.0 (1d8b05cdd 2023-11-20)
+release: 1.75.0
+commit-hash: 1d8b05cdd1287c64467306cf3ca2c8ac60c11eb0
+commit-date: 2023-11-20
+host: x86_64-unknown-linux-gnu",
+        ))
+        .unwrap();
+        Toolchain { rustc, cargo }
+    }
+
+    #[test]
+    fn parses_tool_versions() {
+        let toolchain = toolchain();
+        assert_eq!(toolchain.rustc.release, "1.75.0");
+        assert_eq!(toolchain.rustc.llvm_version.as_deref(), Some("17.0.6"));
+        assert_eq!(toolchain.cargo.llvm_version, None);
+
+        let local_build = ToolVersion::parse(&lines(
+            "rustc 1.77.0-dev\nbinary: rustc\ncommit-hash: unknown\nrelease: 1.77.0-dev",
+        ))
+        .unwrap();
+        assert_eq!(local_build.commit_hash, None);
+        assert_eq!(ToolVersion::parse(&lines("rustc 1.75.0")), None);
+    }
+
+    #[test]
+    fn keeps_build_environment() {
+        let environment = [
+            ("RUSTFLAGS", "-C target-cpu=native"),
+            ("CARGO_PROFILE_RELEASE_LTO", "true"),
+            ("CARGO_PROFILE_RELEASE_INCREMENTAL", "true"),
+            ("CARGO_TARGET_DIR", "/tmp/target"),
+            (
+                "CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_LINKER",
+                "/usr/bin/clang",
+            ),
+            ("CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_RUSTFLAGS", "-C lto"),
+            ("CARGO_REGISTRIES_PRIVATE_TOKEN", "secret"),
+            ("CARGO_BUILD_JOBS", "4"),
+            ("CARGO_BUILD_RUSTC_WRAPPER", "/usr/bin/sccache"),
+            ("HOME", "/root"),
+        ]
+        .map(|(name, value)| (name.to_owned(), value.to_owned()));
+
+        let names: Vec<String> = build_environment(environment)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "CARGO_PROFILE_RELEASE_LTO",
+                "CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_RUSTFLAGS",
+                "RUSTFLAGS"
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_command_line() {
+        let config = SbomConfig {
+            features: Some(Features {
+                all_features: false,
+                no_default_features: true,
+                features: vec!["foo".to_owned(), "bar".to_owned()],
+            }),
+            profile: Some("dist".to_owned()),
+            ..SbomConfig::empty_config()
+        };
+        assert_eq!(
+            build_command(&config, Some("aarch64-apple-darwin")).as_deref(),
+            Some("cargo build --profile dist --target aarch64-apple-darwin --no-default-features --features foo,bar")
+        );
+        let release = SbomConfig {
+            profile: Some("release".to_owned()),
+            ..SbomConfig::empty_config()
+        };
+        assert_eq!(
+            build_command(&release, None).as_deref(),
+            Some("cargo build --release")
+        );
+        assert_eq!(build_command(&SbomConfig::empty_config(), None), None);
+    }
+
+    #[test]
+    fn records_host_and_features_without_profile() {
+        let config = SbomConfig {
+            features: Some(Features {
+                all_features: false,
+                no_default_features: false,
+                features: vec!["foo".to_owned(), "bar".to_owned()],
+            }),
+            ..SbomConfig::empty_config()
+        };
+        let formula = create_formula(&toolchain(), &config, None, &[], []);
+
+        let std = &formula.components.unwrap().0[1];
+        assert_eq!(
+            std.bom_ref.as_deref(),
+            Some("std@1.75.0 x86_64-unknown-linux-gnu")
+        );
+        let workflow = &formula.workflows.unwrap()[0];
+        assert_eq!(workflow.steps, None);
+        assert_eq!(
+            workflow.properties,
+            Some(Properties(vec![
+                Property::new("cdx:cargo:target", "x86_64-unknown-linux-gnu"),
+                Property::new("cdx:cargo:feature", "foo"),
+                Property::new("cdx:cargo:feature", "bar"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn merges_formulas_for_several_targets() {
+        let config = SbomConfig::empty_config();
+        let mut formula = create_formula(
+            &toolchain(),
+            &config,
+            Some("x86_64-unknown-linux-gnu"),
+            &[],
+            [],
+        );
+        let other = create_formula(&toolchain(), &config, Some("aarch64-apple-darwin"), &[], []);
+        merge_formula(&mut formula, &other);
+
+        let refs: Vec<String> = formula
+            .components
+            .unwrap()
+            .0
+            .into_iter()
+            .filter_map(|c| c.bom_ref)
+            .collect();
+        assert_eq!(
+            refs,
+            [
+                "rustc@1.75.0",
+                "std@1.75.0 x86_64-unknown-linux-gnu",
+                "cargo@1.75.0",
+                "std@1.75.0 aarch64-apple-darwin"
+            ]
+        );
+        assert_eq!(formula.workflows.unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,225 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Determines which packages are linked into each target of a package,
+//! based on the unit graph that Cargo computes for `cargo build`.
+//!
+//! The dependencies in `cargo metadata` apply to the package as a whole,
+//! so without the unit graph every binary would list every dependency of the package,
+//! including the ones only used by other binaries or enabled through their `required-features`.
+//!
+//! `--unit-graph` is unstable, so it is only queried when enabled with `--unit-graph`,
+//! which requires a nightly toolchain. Nothing is built, Cargo only prints the graph,
+//! and `--locked` keeps it from updating `Cargo.lock`.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::process::Command;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::config::{SbomConfig, Target};
+use crate::platform::cargo_location;
+
+/// The output of `cargo build --unit-graph`, see
+/// https://doc.rust-lang.org/cargo/reference/unstable.html#unit-graph
+#[derive(Debug, Deserialize)]
+pub struct UnitGraph {
+    units: Vec<Unit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Unit {
+    pkg_id: String,
+    target: UnitTarget,
+    dependencies: Vec<UnitDependency>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UnitTarget {
+    kind: Vec<String>,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct UnitDependency {
+    index: usize,
+}
+
+impl Unit {
+    /// Build scripts and procedural macros run at build time, so neither they
+    /// nor their dependencies are linked into the dependent target
+    fn runs_at_build_time(&self) -> bool {
+        self.target
+            .kind
+            .iter()
+            .any(|kind| kind == "custom-build" || kind == "proc-macro")
+    }
+}
+
+impl UnitGraph {
+    /// Runs `cargo build --unit-graph` for the package or workspace at `manifest_path`
+    /// with the features and target platforms from `config`.
+    ///
+    /// Returns `None` if the graph cannot be computed for the configuration,
+    /// which is the case for `--target all`.
+    pub fn query(
+        manifest_path: &Path,
+        config: &SbomConfig,
+    ) -> Result<Option<Self>, UnitGraphError> {
+        let mut command = Command::new(cargo_location());
+        command
+            .args([
+                "build",
+                "--locked",
+                "--unit-graph",
+                "-Z",
+                "unstable-options",
+            ])
+            .arg("--manifest-path")
+            .arg(manifest_path);
+
+        if let Some(features) = &config.features {
+            if features.all_features {
+                command.arg("--all-features");
+            }
+            if features.no_default_features {
+                command.arg("--no-default-features");
+            }
+            if !features.features.is_empty() {
+                command.arg("--features").arg(features.features.join(","));
+            }
+        }
+
+        match &config.target {
+            Some(Target::SingleTarget(target)) => {
+                command.arg("--target").arg(target);
+            }
+            Some(Target::MultipleTargets(targets)) => {
+                for target in targets {
+                    command.arg("--target").arg(target);
+                }
+            }
+            Some(Target::AllTargets) => return Ok(None),
+            None => (),
+        }
+
+        let output = command.output()?;
+        if !output.status.success() {
+            return Err(UnitGraphError::CargoError(
+                String::from_utf8_lossy(&output.stderr).into_owned(),
+            ));
+        }
+        Ok(Some(Self::parse(&output.stdout)?))
+    }
+
+    pub fn parse(json: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(json)
+    }
+
+    /// Returns the IDs of the packages linked into the target, including the package itself.
+    ///
+    /// Returns `None` if the target is not built, e.g. because its `required-features` are not enabled.
+    pub fn linked_packages(
+        &self,
+        package_id: &str,
+        target_name: &str,
+        target_kind: &[String],
+    ) -> Option<BTreeSet<String>> {
+        let roots: Vec<usize> = self
+            .units
+            .iter()
+            .enumerate()
+            .filter(|(_, unit)| {
+                unit.pkg_id == package_id
+                    && unit.target.name == target_name
+                    && unit.target.kind == target_kind
+            })
+            .map(|(index, _)| index)
+            .collect();
+        if roots.is_empty() {
+            return None;
+        }
+
+        let mut visited = vec![false; self.units.len()];
+        let mut linked = BTreeSet::new();
+        // A target built for several platforms has a root unit for each of them
+        let mut queue = roots;
+        while let Some(index) = queue.pop() {
+            if std::mem::replace(&mut visited[index], true) {
+                continue;
+            }
+            let unit = &self.units[index];
+            linked.insert(unit.pkg_id.clone());
+            for dependency in &unit.dependencies {
+                if !self.units[dependency.index].runs_at_build_time() {
+                    queue.push(dependency.index);
+                }
+            }
+        }
+        Some(linked)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum UnitGraphError {
+    #[error("Failed to invoke cargo")]
+    IoError(#[from] std::io::Error),
+
+    #[error("`cargo build --unit-graph` failed: {}", .0)]
+    CargoError(String),
+
+    #[error("Failed to parse the unit graph")]
+    ParseError(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two binaries of `app` linking `log` and `clap` respectively,
+    /// where `clap` uses a proc-macro and `log` has a build script
+    const UNIT_GRAPH: &str = r#"{
+        "version": 1,
+        "units": [
+            {"pkg_id": "app#0.1.0", "target": {"kind": ["bin"], "name": "server"}, "dependencies": [{"index": 2}]},
+            {"pkg_id": "app#0.1.0", "target": {"kind": ["bin"], "name": "cli"}, "dependencies": [{"index": 4}]},
+            {"pkg_id": "log#0.4.20", "target": {"kind": ["lib"], "name": "log"}, "dependencies": [{"index": 3}]},
+            {"pkg_id": "log#0.4.20", "target": {"kind": ["custom-build"], "name": "build-script-build"}, "dependencies": [{"index": 6}]},
+            {"pkg_id": "clap#4.4.11", "target": {"kind": ["lib"], "name": "clap"}, "dependencies": [{"index": 5}]},
+            {"pkg_id": "clap_derive#4.4.7", "target": {"kind": ["proc-macro"], "name": "clap_derive"}, "dependencies": [{"index": 6}]},
+            {"pkg_id": "cc#1.0.83", "target": {"kind": ["lib"], "name": "cc"}, "dependencies": []}
+        ],
+        "roots": [0, 1]
+    }"#;
+
+    #[test]
+    fn links_only_runtime_dependencies() {
+        let graph = UnitGraph::parse(UNIT_GRAPH.as_bytes()).unwrap();
+        let bin = ["bin".to_owned()];
+
+        let server = graph.linked_packages("app#0.1.0", "server", &bin).unwrap();
+        assert_eq!(Vec::from_iter(server), ["app#0.1.0", "log#0.4.20"]);
+
+        let cli = graph.linked_packages("app#0.1.0", "cli", &bin).unwrap();
+        assert_eq!(Vec::from_iter(cli), ["app#0.1.0", "clap#4.4.11"]);
+
+        assert!(graph.linked_packages("app#0.1.0", "gui", &bin).is_none());
+    }
+}
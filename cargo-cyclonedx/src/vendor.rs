@@ -0,0 +1,256 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Verification of sources vendored with `cargo vendor`.
+//!
+//! When source replacement points Cargo at a vendored directory, `cargo metadata` reports
+//! the manifest path inside that directory, next to the `.cargo-checksum.json` file
+//! that `cargo vendor` writes for every crate.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use cargo_lock::package::Checksum;
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
+use cargo_metadata::Package;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+const CHECKSUM_FILE: &str = ".cargo-checksum.json";
+
+/// The contents of `.cargo-checksum.json`
+#[derive(Debug, Deserialize)]
+struct ChecksumFile {
+    /// SHA-256 hashes of every file in the crate, by path relative to the crate root
+    files: BTreeMap<String, String>,
+    /// SHA-256 hash of the `.crate` archive, absent for crates vendored from git
+    package: Option<String>,
+}
+
+/// A vendored crate whose contents have been verified against `.cargo-checksum.json`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VendoredSource {
+    /// The directory the crate is vendored into
+    pub directory: Utf8PathBuf,
+    /// SHA-256 hash of the `.crate` archive, if it came from a registry
+    pub package_checksum: Option<String>,
+    /// SHA-256 hashes of every file in the crate, by path relative to the crate root
+    pub files: BTreeMap<String, String>,
+}
+
+/// Checks whether the package is vendored, and if so verifies that its contents are unmodified.
+///
+/// `expected_checksum` is the checksum of the package recorded in `Cargo.lock`.
+/// Returns `Ok(None)` if the package is not vendored.
+pub fn verify_vendored_source(
+    package: &Package,
+    expected_checksum: Option<&Checksum>,
+) -> Result<Option<VendoredSource>, VendorError> {
+    let directory = package
+        .manifest_path
+        .parent()
+        .expect("manifest_path in `cargo metadata` output is not a file!");
+    verify_vendored_directory(&package.id.to_string(), directory, expected_checksum)
+}
+
+fn verify_vendored_directory(
+    package: &str,
+    directory: &Utf8Path,
+    expected_checksum: Option<&Checksum>,
+) -> Result<Option<VendoredSource>, VendorError> {
+    let checksum_path = directory.join(CHECKSUM_FILE);
+    if !checksum_path.is_file() {
+        return Ok(None);
+    }
+    log::debug!("Verifying vendored sources of {} in {}", package, directory);
+
+    let contents = std::fs::read_to_string(&checksum_path).map_err(|e| VendorError::IoError {
+        path: checksum_path.clone(),
+        source: e,
+    })?;
+    let checksums: ChecksumFile =
+        serde_json::from_str(&contents).map_err(|e| VendorError::InvalidChecksumFile {
+            path: checksum_path.clone(),
+            source: e,
+        })?;
+
+    let mut problems = Vec::new();
+    if let Some(expected) = expected_checksum {
+        let expected = format!("{expected:x}");
+        match &checksums.package {
+            Some(found) if found.eq_ignore_ascii_case(&expected) => {}
+            Some(found) => problems.push(format!(
+                "the package checksum {found} does not match {expected} from Cargo.lock"
+            )),
+            None => problems.push(format!(
+                "the package checksum is missing, expected {expected} from Cargo.lock"
+            )),
+        }
+    }
+
+    let mut files = BTreeMap::new();
+    for (file, expected) in &checksums.files {
+        let path = directory.join(file);
+        match std::fs::read(&path) {
+            Ok(contents) => {
+                let found = format!("{:x}", Sha256::digest(&contents));
+                if !found.eq_ignore_ascii_case(expected) {
+                    problems.push(format!("{file} has been modified"));
+                }
+                files.insert(file.clone(), found);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                problems.push(format!("{file} is missing"));
+            }
+            Err(e) => return Err(VendorError::IoError { path, source: e }),
+        }
+    }
+
+    // Cargo only checks the files listed in `.cargo-checksum.json`,
+    // but an added file such as `build.rs` can change the build just as much
+    for file in list_files(directory)? {
+        if file != CHECKSUM_FILE && !checksums.files.contains_key(&file) {
+            problems.push(format!("{file} has been added"));
+        }
+    }
+
+    if !problems.is_empty() {
+        let mut details = String::new();
+        for problem in problems {
+            let _ = write!(details, "\n  {problem}");
+        }
+        return Err(VendorError::Modified {
+            package: package.to_owned(),
+            directory: directory.to_owned(),
+            details,
+        });
+    }
+
+    Ok(Some(VendoredSource {
+        directory: directory.to_owned(),
+        package_checksum: checksums.package,
+        files,
+    }))
+}
+
+/// Recursively lists the files in the directory, as `/`-separated paths relative to it
+fn list_files(directory: &Utf8Path) -> Result<Vec<String>, VendorError> {
+    let mut files = Vec::new();
+    let mut queue = vec![directory.to_owned()];
+    while let Some(dir) = queue.pop() {
+        let entries = dir.read_dir_utf8().map_err(|e| VendorError::IoError {
+            path: dir.clone(),
+            source: e,
+        })?;
+        for entry in entries {
+            let entry = entry.map_err(|e| VendorError::IoError {
+                path: dir.clone(),
+                source: e,
+            })?;
+            let file_type = entry.file_type().map_err(|e| VendorError::IoError {
+                path: entry.path().to_owned(),
+                source: e,
+            })?;
+            if file_type.is_dir() {
+                queue.push(entry.path().to_owned());
+            } else {
+                let relative = entry.path().strip_prefix(directory).unwrap();
+                let components: Vec<&str> = relative.components().map(|c| c.as_str()).collect();
+                files.push(components.join("/"));
+            }
+        }
+    }
+    Ok(files)
+}
+
+#[derive(Error, Debug)]
+pub enum VendorError {
+    #[error("Failed to read {path}")]
+    IoError {
+        path: Utf8PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to parse {path}")]
+    InvalidChecksumFile {
+        path: Utf8PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error(
+        "The vendored sources of {package} in {directory} do not match their checksums:{details}"
+    )]
+    Modified {
+        package: String,
+        directory: Utf8PathBuf,
+        details: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn it_should_verify_vendored_sources() {
+        let tmp_dir = assert_fs::TempDir::new().unwrap();
+        let directory = Utf8PathBuf::try_from(tmp_dir.path().to_path_buf()).unwrap();
+        assert!(verify_vendored_directory("pkg", &directory, None)
+            .unwrap()
+            .is_none());
+
+        let lib_hash = format!("{:x}", Sha256::digest(b"pub fn f() {}"));
+        tmp_dir
+            .child("src/lib.rs")
+            .write_str("pub fn f() {}")
+            .unwrap();
+        tmp_dir
+            .child(CHECKSUM_FILE)
+            .write_str(&format!(
+                r#"{{"files":{{"src/lib.rs":"{lib_hash}"}},"package":null}}"#
+            ))
+            .unwrap();
+
+        let vendored = verify_vendored_directory("pkg", &directory, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(vendored.directory, directory);
+        assert_eq!(vendored.package_checksum, None);
+        assert_eq!(vendored.files["src/lib.rs"], lib_hash);
+
+        tmp_dir.child("build.rs").write_str("fn main() {}").unwrap();
+        tmp_dir
+            .child("src/lib.rs")
+            .write_str("pub fn g() {}")
+            .unwrap();
+        let error = verify_vendored_directory("pkg", &directory, None).unwrap_err();
+        let VendorError::Modified { details, .. } = error else {
+            panic!("unexpected error: {error}");
+        };
+        assert_eq!(
+            details,
+            "\n  src/lib.rs has been modified\n  build.rs has been added"
+        );
+
+        tmp_dir.close().unwrap();
+    }
+}
@@ -1,6 +1,9 @@
 use assert_cmd::prelude::*;
 use assert_fs::prelude::*;
+use cyclonedx_bom::models::bom::SpecVersion;
+use cyclonedx_bom::schema::validate_json_with_schema;
 use predicates::prelude::*;
+use sha2::{Digest, Sha256};
 use std::process::Command;
 
 #[test]
@@ -75,6 +78,631 @@ fn find_content_in_bom_files() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn spec_version_1_5_output() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--spec-version=1.5")
+        .arg("--lifecycle=build")
+        .arg("--override-filename=bom");
+
+    cmd.assert().success().stdout("");
+
+    tmp_dir
+        .child("bom.xml")
+        .assert(predicate::str::contains(
+            r#"xmlns="http://cyclonedx.org/schema/bom/1.5""#,
+        ))
+        .assert(predicate::str::contains("<phase>build</phase>"));
+
+    cmd.arg("--format").arg("json");
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read_to_string(tmp_dir.child("bom.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    assert!(validate_json_with_schema(&json, SpecVersion::V1_5).is_ok());
+    assert_eq!(
+        json["metadata"]["tools"]["components"][0]["name"],
+        "cargo-cyclonedx"
+    );
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn workspace_bom_output() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = assert_fs::TempDir::new()?;
+    tmp_dir
+        .child("Cargo.toml")
+        .write_str("[workspace]\nmembers = [\"a\", \"b\"]\nresolver = \"2\"\n")?;
+    tmp_dir.child("a/src/main.rs").touch()?;
+    tmp_dir.child("a/Cargo.toml").write_str(
+        r#"package = { name = "a", version = "0.1.0" }
+dependencies = { b = { path = "../b" } }"#,
+    )?;
+    tmp_dir.child("b/src/lib.rs").touch()?;
+    tmp_dir
+        .child("b/Cargo.toml")
+        .write_str(r#"package = { name = "b", version = "0.2.0" }"#)?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--workspace-bom")
+        .arg("--format=json")
+        .arg("--override-filename=bom");
+
+    cmd.assert().success().stdout("");
+
+    tmp_dir
+        .child("a/a.cdx.json")
+        .assert(predicate::path::missing());
+    tmp_dir
+        .child("b/b.cdx.json")
+        .assert(predicate::path::missing());
+
+    let bom = std::fs::read_to_string(tmp_dir.child("bom.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    let members: Vec<_> = json["metadata"]["component"]["components"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(members, ["a", "b"]);
+    // Members are not repeated as ordinary components
+    assert!(json
+        .get("components")
+        .map_or(true, |c| c.as_array().unwrap().is_empty()));
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn dependency_kinds() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("Cargo.toml").write_str(
+        r#"package = { name = "pkg", version = "0.0.0" }
+build-dependencies = { build-dep = { path = "build-dep" } }
+dev-dependencies = { dev-dep = { path = "dev-dep" } }"#,
+    )?;
+    for dep in ["build-dep", "dev-dep"] {
+        tmp_dir.child(format!("{dep}/src/lib.rs")).touch()?;
+        tmp_dir
+            .child(format!("{dep}/Cargo.toml"))
+            .write_str(&format!(
+                r#"package = {{ name = "{dep}", version = "0.0.0" }}"#
+            ))?;
+    }
+    tmp_dir.child("build.rs").write_str("fn main() {}")?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--include-dev-dependencies")
+        .arg("--override-filename=bom");
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read_to_string(tmp_dir.child("bom.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    let components = json["components"].as_array().unwrap();
+    assert_eq!(components.len(), 2);
+    for component in components {
+        let kind = match component["name"].as_str().unwrap() {
+            "build-dep" => "build",
+            "dev-dep" => "dev",
+            name => panic!("unexpected component {name}"),
+        };
+        assert_eq!(component["scope"], "excluded");
+        assert_eq!(
+            component["properties"][0]["name"],
+            "cdx:cargo:dependency-kind"
+        );
+        assert_eq!(component["properties"][0]["value"], kind);
+    }
+
+    cmd.arg("--exclude-build-dependencies");
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read_to_string(tmp_dir.child("bom.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    let components = json["components"].as_array().unwrap();
+    assert_eq!(components.len(), 1);
+    assert_eq!(components[0]["name"], "dev-dep");
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn active_features() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("Cargo.toml").write_str(
+        r#"package = { name = "pkg", version = "0.0.0" }
+dependencies = { opt = { path = "opt", optional = true, features = ["extra"] } }
+features = { tls = ["dep:opt"] }"#,
+    )?;
+    tmp_dir.child("opt/src/lib.rs").touch()?;
+    tmp_dir.child("opt/Cargo.toml").write_str(
+        r#"package = { name = "opt", version = "0.0.0" }
+features = { extra = [] }"#,
+    )?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--features=tls")
+        .arg("--override-filename=bom");
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read_to_string(tmp_dir.child("bom.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    let properties = |component: &serde_json::Value| -> Vec<(String, String)> {
+        component["properties"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| {
+                let name = p["name"].as_str().unwrap().to_owned();
+                (name, p["value"].as_str().unwrap().to_owned())
+            })
+            .collect()
+    };
+    let property = |name: &str, value: &str| (name.to_owned(), value.to_owned());
+
+    let root_properties = properties(&json["metadata"]["component"]);
+    assert!(root_properties.contains(&property("cdx:cargo:feature", "tls")));
+
+    let opt_properties = properties(&json["components"][0]);
+    assert!(opt_properties.contains(&property("cdx:cargo:optional", "true")));
+    assert!(opt_properties.contains(&property("cdx:cargo:enabled-by", "pkg/tls")));
+    assert!(opt_properties.contains(&property("cdx:cargo:feature", "extra")));
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn patched_dependency() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("Cargo.toml").write_str(
+        r#"package = { name = "pkg", version = "0.0.0" }
+dependencies = { itoa = "1" }
+patch = { crates-io = { itoa = { path = "fork" } } }"#,
+    )?;
+    tmp_dir.child("fork/src/lib.rs").touch()?;
+    tmp_dir
+        .child("fork/Cargo.toml")
+        .write_str(r#"package = { name = "itoa", version = "1.0.99" }"#)?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--override-filename=bom");
+    cmd.assert().success();
+
+    let bom = std::fs::read_to_string(tmp_dir.child("bom.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    let itoa = &json["components"][0];
+    assert_eq!(itoa["modified"], true);
+    // The version that would have been used without the patch is not known
+    let ancestor = &itoa["pedigree"]["ancestors"][0];
+    assert_eq!(ancestor["purl"], "pkg:cargo/itoa");
+    assert_eq!(ancestor["version"], "");
+    assert_eq!(
+        ancestor["properties"],
+        serde_json::json!([{"name": "cdx:cargo:version-requirement", "value": "^1"}])
+    );
+    assert_eq!(itoa["pedigree"]["variants"][0]["purl"], itoa["purl"]);
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+/// Builds a binary with a dependency list in the format used by `cargo auditable`
+#[cfg(target_os = "linux")]
+#[test]
+fn from_binary() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    let version_info = r#"{"packages":[
+        {"name":"app","version":"0.1.0","source":"local","dependencies":[1,2],"root":true},
+        {"name":"itoa","version":"1.0.9","source":"crates.io"},
+        {"name":"cc","version":"1.0.83","source":"crates.io","kind":"build"}
+    ]}"#;
+    let compressed = miniz_oxide::deflate::compress_to_vec_zlib(version_info.as_bytes(), 6);
+    tmp_dir.child("src/main.rs").write_str(&format!(
+        "#[used]\n#[link_section = \".dep-v0\"]\nstatic DEPS: [u8; {}] = {:?};\nfn main() {{}}",
+        compressed.len(),
+        compressed
+    ))?;
+    Command::new(env!("CARGO"))
+        .current_dir(tmp_dir.path())
+        .arg("build")
+        .arg("--quiet")
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--all")
+        .arg("--from-binary")
+        .arg(tmp_dir.child("target/debug/pkg").path());
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read_to_string(tmp_dir.child("target/debug/pkg.cdx.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    assert_eq!(json["metadata"]["component"]["name"], "app");
+    assert_eq!(json["components"][0]["purl"], "pkg:cargo/itoa@1.0.9");
+    assert_eq!(json["components"][0]["scope"], "required");
+    assert_eq!(json["components"][1]["name"], "cc");
+    assert_eq!(json["components"][1]["scope"], "excluded");
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn embed_in_binaries() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("src/main.rs").write_str("fn main() {}")?;
+    Command::new(env!("CARGO"))
+        .current_dir(tmp_dir.path())
+        .arg("build")
+        .arg("--quiet")
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--embed-in-binaries")
+        .arg("target/debug");
+    cmd.assert().success().stdout("");
+
+    let binary = tmp_dir.child("target/debug/pkg");
+    let bom = cyclonedx_bom::embedded::read_from_elf(&std::fs::read(binary.path())?)?;
+    let component = bom.metadata.unwrap().component.unwrap();
+    assert_eq!(component.name.to_string(), "pkg");
+
+    // The binary still works
+    Command::new(binary.path()).assert().success();
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn lockfile_only() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("Cargo.lock").write_str(
+        r#"version = 4
+
+[[package]]
+name = "itoa"
+version = "1.0.11"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "49f1f14873335454500d59611f1cf4a4b0f786f9ac11f4312a78e4cf2566695b"
+
+[[package]]
+name = "pkg"
+version = "0.0.0"
+dependencies = [
+ "itoa",
+]
+"#,
+    )?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--lockfile-only")
+        .arg("--override-filename=bom")
+        // Make sure that cargo is not invoked
+        .env("CARGO", "/nonexistent");
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read_to_string(tmp_dir.child("bom.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    assert_eq!(json["metadata"]["component"]["name"], "pkg");
+    let itoa = &json["components"][0];
+    assert_eq!(itoa["purl"], "pkg:cargo/itoa@1.0.11");
+    assert_eq!(
+        itoa["hashes"][0]["content"],
+        "49f1f14873335454500d59611f1cf4a4b0f786f9ac11f4312a78e4cf2566695b"
+    );
+    assert_eq!(json["dependencies"][0]["dependsOn"][0], itoa["bom-ref"]);
+    assert_eq!(json["compositions"][0]["aggregate"], "incomplete");
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn advisory_db() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("Cargo.lock").write_str(
+        r#"version = 3
+
+[[package]]
+name = "itoa"
+version = "1.0.11"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "pkg"
+version = "0.0.0"
+dependencies = [
+ "itoa",
+]
+"#,
+    )?;
+    tmp_dir
+        .child("advisory-db/crates/itoa/RUSTSEC-2000-0001.md")
+        .write_str(
+            r#"```toml
+[advisory]
+id = "RUSTSEC-2000-0001"
+package = "itoa"
+date = "2000-01-01"
+aliases = ["CVE-2000-0001"]
+cvss = "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"
+
+[versions]
+patched = [">= 1.0.12"]
+```
+
+# Formatting integers executes them
+"#,
+        )?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--lockfile-only")
+        .arg("--spec-version=1.4")
+        .arg("--advisory-db=advisory-db")
+        .arg("--override-filename=bom");
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read_to_string(tmp_dir.child("bom.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    let vulnerability = &json["vulnerabilities"][0];
+    assert_eq!(vulnerability["id"], "RUSTSEC-2000-0001");
+    assert_eq!(vulnerability["source"]["name"], "RustSec");
+    assert_eq!(vulnerability["references"][0]["id"], "CVE-2000-0001");
+    assert_eq!(vulnerability["ratings"][0]["score"], 9.8);
+    assert_eq!(vulnerability["ratings"][0]["severity"], "critical");
+    assert_eq!(vulnerability["ratings"][0]["method"], "CVSSv31");
+    assert_eq!(
+        vulnerability["description"],
+        "Formatting integers executes them"
+    );
+    assert_eq!(
+        vulnerability["affects"][0]["ref"],
+        json["components"][0]["bom-ref"]
+    );
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn undeclared_license_files() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir
+        .child("Cargo.toml")
+        .write_str(r#"package = { name = "pkg", version = "0.0.0", license = "MIT" }"#)?;
+    tmp_dir
+        .child("LICENSE-MIT")
+        .write_str("MIT License\n\nCopyright (c) 2024 The pkg Developers")?;
+    tmp_dir.child("src/main.rs").write_str(
+        "// Copyright 2023 The pkg Developers\n// SPDX-FileCopyrightText: Jane Doe\nfn main() {}",
+    )?;
+    tmp_dir
+        .child("LICENSE-APACHE")
+        .write_str("Apache License")?;
+    tmp_dir
+        .child("NOTICE")
+        .write_str("pkg includes software developed by Jane Doe.")?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json");
+    cmd.assert().success().stderr(predicate::str::contains(
+        "LICENSE-APACHE suggests Apache-2.0, which is not in the declared license MIT",
+    ));
+
+    let bom = std::fs::read_to_string(tmp_dir.child("pkg.cdx.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    let component = &json["metadata"]["component"];
+    // Only the declared license is recorded, the files are listed as properties
+    let licenses = component["licenses"].as_array().unwrap();
+    assert_eq!(licenses.len(), 1);
+    assert_eq!(licenses[0]["expression"], "MIT");
+    let property_values = |name: &str| -> Vec<String> {
+        component["properties"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|p| p["name"] == name)
+            .map(|p| p["value"].as_str().unwrap().to_owned())
+            .collect()
+    };
+    assert_eq!(
+        property_values("cdx:cargo:license-file"),
+        ["LICENSE-APACHE", "LICENSE-MIT"]
+    );
+    assert_eq!(property_values("cdx:cargo:notice-file"), ["NOTICE"]);
+    // The texts of the files are attached to the license evidence, even before CycloneDX 1.5
+    let evidence: Vec<(&str, &str)> = component["evidence"]["licenses"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| {
+            let license = &l["license"];
+            let id = license["id"].as_str().or(license["name"].as_str());
+            (id.unwrap(), license["text"]["content"].as_str().unwrap())
+        })
+        .collect();
+    // Base64-encoded, so the line breaks are preserved
+    assert_eq!(
+        evidence,
+        [
+            ("Apache-2.0", "QXBhY2hlIExpY2Vuc2U="),
+            (
+                "MIT",
+                "TUlUIExpY2Vuc2UKCkNvcHlyaWdodCAoYykgMjAyNCBUaGUgcGtnIERldmVsb3BlcnM="
+            ),
+            (
+                "NOASSERTION",
+                "cGtnIGluY2x1ZGVzIHNvZnR3YXJlIGRldmVsb3BlZCBieSBKYW5lIERvZS4="
+            ),
+        ]
+    );
+    assert_eq!(property_values("cdx:cargo:license-mismatch").len(), 1);
+    assert_eq!(
+        component["copyright"],
+        "Copyright (c) 2024 The pkg Developers; Copyright Jane Doe"
+    );
+    let copyrights = component["evidence"]["copyright"].as_array().unwrap();
+    assert_eq!(copyrights.len(), 3);
+    assert_eq!(copyrights[1]["text"], "Copyright 2023 The pkg Developers");
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn license_file_identification() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("Cargo.toml").write_str(
+        r#"package = { name = "pkg", version = "0.0.0", license-file = "legal.txt" }"#,
+    )?;
+    tmp_dir.child("legal.txt").write_str(
+        "Copyright (c) 2024 Someone
+
+Permission to use, copy, modify, and/or distribute this software for any
+purpose with or without fee is hereby granted, provided that the above
+copyright notice and this permission notice appear in all copies.
+
+THE SOFTWARE IS PROVIDED \"AS IS\" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+",
+    )?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--spec-version=1.5");
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read_to_string(tmp_dir.child("pkg.cdx.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    let component = &json["metadata"]["component"];
+    assert_eq!(component["licenses"][0]["license"]["id"], "ISC");
+    let evidence = &component["evidence"]["licenses"][0]["license"];
+    assert_eq!(evidence["id"], "ISC");
+    // The text is only attached to the declared license
+    assert_eq!(
+        component["licenses"][0]["license"]["text"]["encoding"],
+        "base64"
+    );
+    assert!(evidence["text"].is_null());
+    assert_eq!(evidence["properties"][0]["value"], "legal.txt");
+    assert_eq!(
+        evidence["properties"][1]["name"],
+        "cdx:cargo:license-confidence"
+    );
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn multiple_targets() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("Cargo.toml").write_str(
+        r#"package = { name = "pkg", version = "0.0.0" }
+target.'cfg(windows)'.dependencies = { win-dep = { path = "win-dep" } }"#,
+    )?;
+    tmp_dir.child("win-dep/src/lib.rs").touch()?;
+    tmp_dir
+        .child("win-dep/Cargo.toml")
+        .write_str(r#"package = { name = "win-dep", version = "0.0.0" }"#)?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--target=x86_64-pc-windows-msvc")
+        .arg("--target=x86_64-unknown-linux-gnu")
+        .arg("--combine-targets");
+    cmd.assert().success().stdout("");
+
+    let read_components =
+        |filename: &str| -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+            let bom = std::fs::read_to_string(tmp_dir.child(filename).path())?;
+            let json: serde_json::Value = serde_json::from_str(&bom)?;
+            Ok(json["components"].as_array().cloned().unwrap_or_default())
+        };
+
+    let windows = read_components("pkg_x86_64-pc-windows-msvc.cdx.json")?;
+    assert_eq!(windows.len(), 1);
+    assert_eq!(windows[0]["name"], "win-dep");
+
+    let linux = read_components("pkg_x86_64-unknown-linux-gnu.cdx.json")?;
+    assert!(linux.is_empty());
+
+    let combined = read_components("pkg_combined.cdx.json")?;
+    assert_eq!(combined.len(), 1);
+    let properties = combined[0]["properties"].as_array().unwrap();
+    assert!(properties
+        .iter()
+        .any(|p| p["name"] == "cdx:cargo:target" && p["value"] == "x86_64-pc-windows-msvc"));
+    assert!(properties
+        .iter()
+        .any(|p| p["name"] == "cdx:cargo:edge-targets"
+            && p["value"]
+                .as_str()
+                .unwrap()
+                .ends_with(" x86_64-pc-windows-msvc")));
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
 #[test]
 fn find_content_in_stderr() -> Result<(), Box<dyn std::error::Error>> {
     let tmp_dir = make_temp_rust_project()?;
@@ -133,6 +761,536 @@ fn find_content_in_stderr() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn reproducible() -> Result<(), Box<dyn std::error::Error>> {
+    let generate = |tmp_dir: &assert_fs::TempDir| -> Result<String, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+        cmd.current_dir(tmp_dir.path())
+            .env("SOURCE_DATE_EPOCH", "1700000000")
+            .arg("cyclonedx")
+            .arg("--format=json")
+            .arg("--reproducible");
+        cmd.assert().success().stdout("");
+        Ok(std::fs::read_to_string(
+            tmp_dir.child("pkg.cdx.json").path(),
+        )?)
+    };
+    let make_project = || -> Result<assert_fs::TempDir, Box<dyn std::error::Error>> {
+        let tmp_dir = make_temp_rust_project()?;
+        tmp_dir.child("Cargo.toml").write_str(
+            r#"
+            [package]
+            name = "pkg"
+            version = "0.0.0"
+
+            [dependencies]
+            dep = { path = "dep" }
+            "#,
+        )?;
+        tmp_dir
+            .child("dep/Cargo.toml")
+            .write_str(r#"package = { name = "dep", version = "0.0.0" }"#)?;
+        tmp_dir.child("dep/src/lib.rs").touch()?;
+        Ok(tmp_dir)
+    };
+
+    let first_dir = make_project()?;
+    let first = generate(&first_dir)?;
+    // The SBOM written by the first run must not affect the second one
+    assert_eq!(first, generate(&first_dir)?);
+
+    let second_dir = make_project()?;
+    assert_eq!(first, generate(&second_dir)?);
+
+    let json: serde_json::Value = serde_json::from_str(&first)?;
+    assert_eq!(json["metadata"]["timestamp"], "2023-11-14T22:13:20Z");
+    assert_eq!(
+        json["metadata"]["component"]["bom-ref"],
+        "path+file://.#pkg@0.0.0"
+    );
+    assert_eq!(json["components"][0]["bom-ref"], "path+file://dep#0.0.0");
+    assert!(json["serialNumber"]
+        .as_str()
+        .is_some_and(|serial| serial.starts_with("urn:uuid:")));
+
+    first_dir.close()?;
+    second_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn reproducible_with_overridden_filename() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    let generate = || -> Result<String, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+        cmd.current_dir(tmp_dir.path())
+            .env("SOURCE_DATE_EPOCH", "1700000000")
+            .arg("cyclonedx")
+            .arg("--format=json")
+            .arg("--override-filename=bom")
+            .arg("--reproducible");
+        cmd.assert().success().stdout("");
+        Ok(std::fs::read_to_string(tmp_dir.child("bom.json").path())?)
+    };
+
+    let first = generate()?;
+    let json: serde_json::Value = serde_json::from_str(&first)?;
+    assert!(json["metadata"]["component"]["hashes"][0]["content"].is_string());
+    // The `bom.json` written by the first run is not part of the package sources
+    assert_eq!(first, generate()?);
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn toolchain_formulation() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("Cargo.toml").write_str(
+        r#"
+        [package]
+        name = "pkg"
+        version = "0.0.0"
+
+        [features]
+        extra = []
+
+        [profile.dist]
+        inherits = "release"
+        lto = "thin"
+        codegen-units = 1
+        "#,
+    )?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .env("RUSTFLAGS", "-C debuginfo=0")
+        .env("CARGO_REGISTRY_TOKEN", "secret")
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--spec-version=1.5")
+        .arg("--target=x86_64-unknown-linux-gnu")
+        .arg("--profile=dist");
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read_to_string(tmp_dir.child("pkg.cdx.json").path())?;
+    assert!(!bom.contains("secret"));
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    let formula = &json["formulation"][0];
+    let names: Vec<&str> = formula["components"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|component| component["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["rustc", "std", "cargo"]);
+
+    let workflow = &formula["workflows"][0];
+    assert_eq!(
+        workflow["steps"][0]["commands"][0]["executed"],
+        "cargo build --profile dist --target x86_64-unknown-linux-gnu"
+    );
+    assert_eq!(
+        workflow["inputs"][0]["environmentVars"][0],
+        serde_json::json!({"name": "RUSTFLAGS", "value": "-C debuginfo=0"})
+    );
+    let properties = workflow["properties"].as_array().unwrap();
+    for (name, value) in [
+        ("cdx:cargo:profile", "dist"),
+        ("cdx:cargo:profile:inherits", "release"),
+        ("cdx:cargo:profile:lto", "thin"),
+        ("cdx:cargo:profile:codegen-units", "1"),
+        ("cdx:cargo:target", "x86_64-unknown-linux-gnu"),
+    ] {
+        assert!(properties.contains(&serde_json::json!({"name": name, "value": value})));
+    }
+
+    // Without a profile the build command is not known, but the features and the host are
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--spec-version=1.5")
+        .arg("--features=extra");
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read_to_string(tmp_dir.child("pkg.cdx.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    let formula = &json["formulation"][0];
+    let workflow = &formula["workflows"][0];
+    assert!(workflow["steps"].is_null());
+    assert!(!bom.contains("cdx:cargo:profile"));
+    let properties = workflow["properties"].as_array().unwrap();
+    assert!(
+        properties.contains(&serde_json::json!({"name": "cdx:cargo:feature", "value": "extra"}))
+    );
+    let host = properties
+        .iter()
+        .find(|property| property["name"] == "cdx:cargo:target")
+        .unwrap()["value"]
+        .as_str()
+        .unwrap();
+    assert_eq!(
+        formula["components"][1]["bom-ref"],
+        format!(
+            "std@{} {host}",
+            formula["components"][1]["version"].as_str().unwrap()
+        )
+    );
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn native_libraries() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("Cargo.toml").write_str(
+        r#"
+        [package]
+        name = "pkg"
+        version = "0.0.0"
+
+        [dependencies]
+        foo-sys = { path = "foo-sys" }
+        "#,
+    )?;
+    tmp_dir.child("foo-sys/Cargo.toml").write_str(
+        r#"
+        [package]
+        name = "foo-sys"
+        version = "0.1.0+1.2.3"
+        links = "foo"
+        "#,
+    )?;
+    tmp_dir
+        .child("foo-sys/build.rs")
+        .write_str("fn main() {}")?;
+    tmp_dir.child("foo-sys/src/lib.rs").touch()?;
+    tmp_dir.child("foo-sys/foo/src/foo.c").touch()?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json");
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read_to_string(tmp_dir.child("pkg.cdx.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    let components = json["components"].as_array().unwrap();
+    let foo_sys = components
+        .iter()
+        .find(|component| component["name"] == "foo-sys")
+        .unwrap();
+    assert!(foo_sys.get("externalReferences").is_none());
+
+    let foo = components
+        .iter()
+        .find(|component| component["name"] == "foo")
+        .unwrap();
+    assert_eq!(foo["type"], "library");
+    assert_eq!(foo["version"], "1.2.3");
+    assert_eq!(foo["scope"], "required");
+    assert_eq!(
+        foo["properties"][0],
+        serde_json::json!({"name": "cdx:cargo:bundled-source", "value": "foo"})
+    );
+
+    let foo_sys_dependencies = json["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|dependency| dependency["ref"] == foo_sys["bom-ref"])
+        .unwrap();
+    assert_eq!(foo_sys_dependencies["dependsOn"][0], foo["bom-ref"]);
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn per_binary_dependencies() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("Cargo.toml").write_str(
+        r#"package = { name = "pkg", version = "0.0.0" }
+dependencies = { common = { path = "common" }, opt = { path = "opt", optional = true } }
+build-dependencies = { gen = { path = "gen" } }
+features = { tls = ["dep:opt"] }
+bin = [{ name = "server", path = "src/server.rs" }, { name = "tool", path = "src/tool.rs", required-features = ["tls"] }]"#,
+    )?;
+    tmp_dir.child("build.rs").write_str("fn main() {}")?;
+    tmp_dir.child("src/server.rs").write_str("fn main() {}")?;
+    tmp_dir.child("src/tool.rs").write_str("fn main() {}")?;
+    for name in ["common", "opt", "gen"] {
+        tmp_dir.child(format!("{name}/src/lib.rs")).touch()?;
+        tmp_dir
+            .child(format!("{name}/Cargo.toml"))
+            .write_str(&format!(
+                r#"package = {{ name = "{name}", version = "0.0.0" }}"#
+            ))?;
+    }
+
+    let component_names = |file: &str| -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let bom = std::fs::read_to_string(tmp_dir.child(file).path())?;
+        let json: serde_json::Value = serde_json::from_str(&bom)?;
+        Ok(json["components"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["name"].as_str().unwrap().to_owned())
+            .collect())
+    };
+
+    // Without the unit graph every target lists all dependencies of the package
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--describe=binaries");
+    cmd.assert().success().stdout("");
+    assert_eq!(component_names("tool_bin.cdx.json")?, ["common", "gen"]);
+    std::fs::remove_file(tmp_dir.child("tool_bin.cdx.json").path())?;
+
+    // `--unit-graph` is unstable, so enable it on stable toolchains as well
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .env("RUSTC_BOOTSTRAP", "1")
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--describe=binaries")
+        .arg("--unit-graph");
+    cmd.assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("Not describing target tool"));
+
+    // The build dependency is not linked into the binary
+    assert_eq!(component_names("server_bin.cdx.json")?, ["common"]);
+    tmp_dir
+        .child("tool_bin.cdx.json")
+        .assert(predicate::path::missing());
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .env("RUSTC_BOOTSTRAP", "1")
+        .arg("--describe=binaries")
+        .arg("--unit-graph")
+        .arg("--features=tls");
+    cmd.assert().success().stdout("");
+
+    // Enabling a feature makes the optional dependency available to every target
+    assert_eq!(component_names("server_bin.cdx.json")?, ["common", "opt"]);
+    assert_eq!(component_names("tool_bin.cdx.json")?, ["common", "opt"]);
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn build_messages() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("Cargo.toml").write_str(
+        r#"package = { name = "pkg", version = "0.0.0" }
+dependencies = { common = { path = "common" } }
+target.'cfg(windows)'.dependencies = { win = { path = "win" } }"#,
+    )?;
+    tmp_dir.child("src/main.rs").write_str("fn main() {}")?;
+    for name in ["common", "win"] {
+        tmp_dir.child(format!("{name}/src/lib.rs")).touch()?;
+        tmp_dir
+            .child(format!("{name}/Cargo.toml"))
+            .write_str(&format!(
+                r#"package = {{ name = "{name}", version = "0.0.0" }}"#
+            ))?;
+    }
+    let output = Command::new(env!("CARGO"))
+        .current_dir(tmp_dir.path())
+        .arg("build")
+        .arg("--quiet")
+        .arg("--message-format=json")
+        .output()?;
+    assert!(output.status.success());
+    let messages = tmp_dir.child("messages.json");
+    messages.write_binary(&output.stdout)?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--target=all")
+        .arg("--override-filename=bom")
+        .arg("--build-messages=-")
+        .stdin(std::fs::File::open(messages.path())?);
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read_to_string(tmp_dir.child("bom.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+
+    // The Windows-only dependency has not been compiled
+    let names: Vec<&str> = json["components"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["common"]);
+
+    let binary = &json["metadata"]["component"]["components"][0];
+    assert_eq!(binary["name"], "pkg");
+    let executable = std::str::from_utf8(&output.stdout)?
+        .lines()
+        .map(serde_json::from_str::<serde_json::Value>)
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .find_map(|message| message["executable"].as_str().map(str::to_owned))
+        .unwrap();
+    let hash = format!("{:x}", Sha256::digest(std::fs::read(executable)?));
+    assert_eq!(binary["hashes"][0]["content"], hash);
+
+    // With `--workspace-bom` the targets of the members are nested in the member components
+    tmp_dir.child("Cargo.toml").write_str(
+        r#"package = { name = "pkg", version = "0.0.0" }
+workspace = { members = ["tool"] }
+dependencies = { common = { path = "common" } }"#,
+    )?;
+    tmp_dir
+        .child("tool/src/main.rs")
+        .write_str("fn main() {}")?;
+    tmp_dir
+        .child("tool/Cargo.toml")
+        .write_str(r#"package = { name = "tool", version = "0.0.0" }"#)?;
+    let output = Command::new(env!("CARGO"))
+        .current_dir(tmp_dir.path())
+        .arg("build")
+        .arg("--quiet")
+        .arg("--workspace")
+        .arg("--message-format=json")
+        .output()?;
+    assert!(output.status.success());
+    messages.write_binary(&output.stdout)?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--workspace-bom")
+        .arg("--override-filename=bom")
+        .arg("--build-messages")
+        .arg(messages.path());
+    cmd.assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("ignoring them").not());
+
+    let bom = std::fs::read_to_string(tmp_dir.child("bom.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    let subcomponents = json["metadata"]["component"]["components"]
+        .as_array()
+        .unwrap();
+    let tool = subcomponents
+        .iter()
+        .find(|c| c["name"] == "tool" && c["components"].is_array())
+        .unwrap();
+    assert!(tool["components"][0]["hashes"][0]["content"].is_string());
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn source_evidence() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("Cargo.toml").write_str(
+        r#"package = { name = "pkg", version = "0.0.0" }
+dependencies = { dep = { path = "crates/dep" } }"#,
+    )?;
+    tmp_dir.child("crates/dep/src/lib.rs").touch()?;
+    tmp_dir
+        .child("crates/dep/Cargo.toml")
+        .write_str(r#"package = { name = "dep", version = "0.0.0" }"#)?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--spec-version=1.5")
+        .arg("--override-filename=bom");
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read_to_string(tmp_dir.child("bom.json").path())?;
+    let json: serde_json::Value = serde_json::from_str(&bom)?;
+    assert!(validate_json_with_schema(&json, SpecVersion::V1_5).is_ok());
+
+    let root = &json["metadata"]["component"]["evidence"];
+    assert_eq!(root["occurrences"][0]["location"], ".");
+
+    let dep = &json["components"][0]["evidence"];
+    assert_eq!(dep["occurrences"][0]["location"], "crates/dep");
+    assert_eq!(dep["identity"]["field"], "purl");
+    assert_eq!(
+        dep["identity"]["methods"][0]["technique"],
+        "manifest-analysis"
+    );
+    assert_eq!(dep["identity"]["methods"][0]["value"], "cargo metadata");
+    assert_eq!(dep["identity"]["confidence"], 0.9);
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn manifest_properties() -> Result<(), Box<dyn std::error::Error>> {
+    use cyclonedx_bom::cargo_properties::{CargoProperties, Publish};
+
+    let tmp_dir = make_temp_rust_project()?;
+    tmp_dir.child("Cargo.toml").write_str(
+        r#"[package]
+name = "pkg"
+version = "0.0.0"
+edition = "2021"
+rust-version = "1.70"
+categories = ["command-line-utilities"]
+keywords = ["sbom", "cyclonedx"]
+readme = "docs/README.md"
+publish = false
+default-run = "pkg""#,
+    )?;
+    tmp_dir.child("src/main.rs").write_str("fn main() {}")?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.current_dir(tmp_dir.path())
+        .arg("cyclonedx")
+        .arg("--format=json")
+        .arg("--override-filename=bom");
+    cmd.assert().success().stdout("");
+
+    let bom = std::fs::read(tmp_dir.child("bom.json").path())?;
+    let bom = cyclonedx_bom::models::bom::Bom::parse_from_json(&bom[..])?;
+    let component = bom.metadata.unwrap().component.unwrap();
+    let cargo = CargoProperties::of(&component);
+    assert_eq!(cargo.edition(), Some("2021"));
+    assert_eq!(cargo.rust_version(), Some("1.70.0"));
+    assert_eq!(cargo.categories(), ["command-line-utilities"]);
+    assert_eq!(cargo.keywords(), ["sbom", "cyclonedx"]);
+    assert_eq!(cargo.readme(), Some("docs/README.md"));
+    assert_eq!(cargo.publish(), Publish::Never);
+    assert_eq!(cargo.default_run(), Some("pkg"));
+    assert_eq!(cargo.registry(), None);
+
+    tmp_dir.close()?;
+
+    Ok(())
+}
+
 fn make_temp_rust_project() -> Result<assert_fs::TempDir, assert_fs::fixture::FixtureError> {
     let tmp_dir = assert_fs::TempDir::new()?;
     tmp_dir.child("src/main.rs").touch()?;
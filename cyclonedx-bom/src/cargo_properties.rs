@@ -0,0 +1,249 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Typed access to the `cdx:cargo:` and `cdx:rust:` properties that `cargo cyclonedx`
+//! records on the components describing Rust crates.
+//!
+//! `cdx:rust:` properties describe the crate as Rust code, `cdx:cargo:` properties
+//! describe how Cargo builds and resolves it. Properties that can occur several times
+//! on the same component, such as the enabled features, are read back as lists.
+//!
+//! ```
+//! use cyclonedx_bom::cargo_properties::{CargoProperties, DependencyKind};
+//! use cyclonedx_bom::models::property::{Properties, Property};
+//!
+//! let properties = Properties(vec![
+//!     Property::new("cdx:rust:edition", "2021"),
+//!     Property::new("cdx:cargo:dependency-kind", "normal"),
+//!     Property::new("cdx:cargo:feature", "std"),
+//!     Property::new("cdx:cargo:feature", "derive"),
+//! ]);
+//! let cargo = CargoProperties::new(Some(&properties));
+//! assert_eq!(cargo.edition(), Some("2021"));
+//! assert_eq!(cargo.dependency_kind(), Some(DependencyKind::Normal));
+//! assert_eq!(cargo.features(), ["std", "derive"]);
+//! ```
+
+use crate::models::component::Component;
+use crate::models::property::Properties;
+
+/// The names of the properties, for reading or writing them directly
+pub mod names {
+    pub const EDITION: &str = "cdx:rust:edition";
+    pub const RUST_VERSION: &str = "cdx:rust:rust-version";
+    pub const CATEGORY: &str = "cdx:cargo:category";
+    pub const KEYWORD: &str = "cdx:cargo:keyword";
+    pub const README: &str = "cdx:cargo:readme";
+    pub const PUBLISH: &str = "cdx:cargo:publish";
+    pub const DEFAULT_RUN: &str = "cdx:cargo:default-run";
+    pub const REGISTRY: &str = "cdx:cargo:registry";
+    pub const DEPENDENCY_KIND: &str = "cdx:cargo:dependency-kind";
+    pub const OPTIONAL: &str = "cdx:cargo:optional";
+    pub const PROC_MACRO: &str = "cdx:cargo:proc-macro";
+    pub const ENABLED_BY: &str = "cdx:cargo:enabled-by";
+    pub const FEATURE: &str = "cdx:cargo:feature";
+    pub const CFG: &str = "cdx:cargo:cfg";
+    pub const TARGET: &str = "cdx:cargo:target";
+    pub const GIT_REF: &str = "cdx:cargo:git-ref";
+    pub const GIT_COMMIT: &str = "cdx:cargo:git-commit";
+}
+
+/// How a crate is used by the crates depending on it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DependencyKind {
+    /// Compiled into the artifact
+    Normal,
+    /// Only used by build scripts or procedural macros
+    Build,
+    /// Only used by tests, examples and benchmarks
+    Dev,
+}
+
+impl DependencyKind {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "normal" => Some(Self::Normal),
+            "build" => Some(Self::Build),
+            "dev" => Some(Self::Dev),
+            _ => None,
+        }
+    }
+}
+
+/// The registries a crate may be published to, as restricted by `publish` in its `Cargo.toml`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Publish {
+    /// Any registry, which is the default
+    Unrestricted,
+    /// No registry, i.e. `publish = false`
+    Never,
+    /// Only the registries with the given names
+    Registries(Vec<String>),
+}
+
+/// A view of the `cdx:cargo:` and `cdx:rust:` properties of a component
+#[derive(Clone, Copy, Debug)]
+pub struct CargoProperties<'a> {
+    properties: Option<&'a Properties>,
+}
+
+impl<'a> CargoProperties<'a> {
+    pub fn new(properties: Option<&'a Properties>) -> Self {
+        Self { properties }
+    }
+
+    pub fn of(component: &'a Component) -> Self {
+        Self::new(component.properties.as_ref())
+    }
+
+    fn value(&self, name: &'a str) -> Option<&'a str> {
+        self.properties
+            .and_then(|properties| properties.value(name))
+    }
+
+    fn values(&self, name: &'a str) -> Vec<&'a str> {
+        self.properties
+            .map(|properties| properties.values(name).collect())
+            .unwrap_or_default()
+    }
+
+    /// The Rust edition of the crate, e.g. `2021`
+    pub fn edition(&self) -> Option<&'a str> {
+        self.value(names::EDITION)
+    }
+
+    /// The minimum supported Rust version declared with `rust-version`, as a full version such as `1.70.0`
+    pub fn rust_version(&self) -> Option<&'a str> {
+        self.value(names::RUST_VERSION)
+    }
+
+    /// The crates.io categories of the crate
+    pub fn categories(&self) -> Vec<&'a str> {
+        self.values(names::CATEGORY)
+    }
+
+    /// The keywords of the crate
+    pub fn keywords(&self) -> Vec<&'a str> {
+        self.values(names::KEYWORD)
+    }
+
+    /// The path of the README file, relative to the crate root
+    pub fn readme(&self) -> Option<&'a str> {
+        self.value(names::README)
+    }
+
+    pub fn publish(&self) -> Publish {
+        let registries = self.values(names::PUBLISH);
+        match registries.as_slice() {
+            [] => Publish::Unrestricted,
+            ["false"] => Publish::Never,
+            registries => Publish::Registries(registries.iter().map(|r| r.to_string()).collect()),
+        }
+    }
+
+    /// The binary `cargo run` runs by default
+    pub fn default_run(&self) -> Option<&'a str> {
+        self.value(names::DEFAULT_RUN)
+    }
+
+    /// The registry the crate comes from: `crates-io` for crates.io, or the URL of the index
+    pub fn registry(&self) -> Option<&'a str> {
+        self.value(names::REGISTRY)
+    }
+
+    pub fn dependency_kind(&self) -> Option<DependencyKind> {
+        self.value(names::DEPENDENCY_KIND)
+            .and_then(DependencyKind::parse)
+    }
+
+    pub fn is_optional(&self) -> bool {
+        self.value(names::OPTIONAL) == Some("true")
+    }
+
+    pub fn is_proc_macro(&self) -> bool {
+        self.value(names::PROC_MACRO) == Some("true")
+    }
+
+    /// The features of dependent crates that enabled this optional dependency, e.g. `reqwest/native-tls`
+    pub fn enabled_by(&self) -> Vec<&'a str> {
+        self.values(names::ENABLED_BY)
+    }
+
+    /// The Cargo features enabled for the crate
+    pub fn features(&self) -> Vec<&'a str> {
+        self.values(names::FEATURE)
+    }
+
+    /// The `cfg()` predicates or target triples under which the crate is used.
+    /// Empty if the crate is used on all targets.
+    pub fn cfgs(&self) -> Vec<&'a str> {
+        self.values(names::CFG)
+    }
+
+    /// The target platforms the crate is used on, in SBOMs combining several targets
+    pub fn targets(&self) -> Vec<&'a str> {
+        self.values(names::TARGET)
+    }
+
+    /// The branch, tag or revision requested for a git dependency, e.g. `branch=main`
+    pub fn git_ref(&self) -> Option<&'a str> {
+        self.value(names::GIT_REF)
+    }
+
+    /// The locked commit of a git dependency
+    pub fn git_commit(&self) -> Option<&'a str> {
+        self.value(names::GIT_COMMIT)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::property::Property;
+
+    #[test]
+    fn it_should_read_publish_restrictions() {
+        let publish = |values: &[&str]| {
+            let properties = Properties(
+                values
+                    .iter()
+                    .map(|value| Property::new(names::PUBLISH, value))
+                    .collect(),
+            );
+            CargoProperties::new(Some(&properties)).publish()
+        };
+
+        assert_eq!(publish(&[]), Publish::Unrestricted);
+        assert_eq!(publish(&["false"]), Publish::Never);
+        assert_eq!(
+            publish(&["internal", "crates-io"]),
+            Publish::Registries(vec!["internal".to_owned(), "crates-io".to_owned()])
+        );
+    }
+
+    #[test]
+    fn it_should_handle_missing_properties() {
+        let cargo = CargoProperties::new(None);
+
+        assert_eq!(cargo.edition(), None);
+        assert_eq!(cargo.dependency_kind(), None);
+        assert!(!cargo.is_optional());
+        assert!(cargo.features().is_empty());
+        assert_eq!(cargo.publish(), Publish::Unrestricted);
+    }
+}
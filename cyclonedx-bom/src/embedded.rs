@@ -0,0 +1,334 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Reading SBOMs embedded into ELF binaries.
+//!
+//! The SBOM is stored as a JSON document in a non-allocated section named [`ELF_SECTION_NAME`],
+//! so it is not loaded into memory when the binary runs. It can also be extracted with
+//! `objcopy --dump-section .cyclonedx=bom.json <binary>`.
+
+use crate::errors::EmbeddedBomReadError;
+use crate::models::bom::Bom;
+
+/// The name of the ELF section holding the embedded SBOM
+pub const ELF_SECTION_NAME: &str = ".cyclonedx";
+
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+const ELFCLASS32: u8 = 1;
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const ELFDATA2MSB: u8 = 2;
+const SHT_NOBITS: u32 = 8;
+/// Indicates that the real value of a field is stored in the first section header
+const SHN_XINDEX: u16 = 0xffff;
+
+/// Extracts the SBOM embedded into an ELF binary
+pub fn read_from_elf(binary: &[u8]) -> Result<Bom, EmbeddedBomReadError> {
+    let data =
+        find_elf_section(binary, ELF_SECTION_NAME)?.ok_or(EmbeddedBomReadError::SectionMissing)?;
+    Ok(Bom::parse_from_json(data)?)
+}
+
+/// The layout of an ELF file, which depends on its class and endianness
+struct Elf<'a> {
+    data: &'a [u8],
+    is_64: bool,
+    is_big_endian: bool,
+}
+
+impl<'a> Elf<'a> {
+    fn bytes(&self, offset: u64, len: u64) -> Result<&'a [u8], EmbeddedBomReadError> {
+        let start = usize::try_from(offset).map_err(|_| truncated())?;
+        let len = usize::try_from(len).map_err(|_| truncated())?;
+        let end = start.checked_add(len).ok_or_else(truncated)?;
+        self.data.get(start..end).ok_or_else(truncated)
+    }
+
+    fn u16(&self, offset: u64) -> Result<u16, EmbeddedBomReadError> {
+        let bytes = self.bytes(offset, 2)?.try_into().unwrap();
+        Ok(match self.is_big_endian {
+            true => u16::from_be_bytes(bytes),
+            false => u16::from_le_bytes(bytes),
+        })
+    }
+
+    fn u32(&self, offset: u64) -> Result<u32, EmbeddedBomReadError> {
+        let bytes = self.bytes(offset, 4)?.try_into().unwrap();
+        Ok(match self.is_big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        })
+    }
+
+    fn u64(&self, offset: u64) -> Result<u64, EmbeddedBomReadError> {
+        let bytes = self.bytes(offset, 8)?.try_into().unwrap();
+        Ok(match self.is_big_endian {
+            true => u64::from_be_bytes(bytes),
+            false => u64::from_le_bytes(bytes),
+        })
+    }
+
+    /// Reads a field that is 4 bytes long in 32-bit files and 8 bytes long in 64-bit ones
+    fn word(&self, offset32: u64, offset64: u64) -> Result<u64, EmbeddedBomReadError> {
+        match self.is_64 {
+            true => self.u64(offset64),
+            false => self.u32(offset32).map(u64::from),
+        }
+    }
+}
+
+/// A section header, reduced to the fields needed to locate the section data
+struct SectionHeader {
+    name: u32,
+    section_type: u32,
+    offset: u64,
+    size: u64,
+    link: u32,
+}
+
+fn truncated() -> EmbeddedBomReadError {
+    EmbeddedBomReadError::MalformedElf("the file is truncated".to_string())
+}
+
+/// Returns the contents of the section with the given name, or `None` if there is no such section
+fn find_elf_section<'a>(
+    binary: &'a [u8],
+    name: &str,
+) -> Result<Option<&'a [u8]>, EmbeddedBomReadError> {
+    if !binary.starts_with(ELF_MAGIC) || binary.len() < 6 {
+        return Err(EmbeddedBomReadError::NotElf);
+    }
+    let elf = Elf {
+        data: binary,
+        is_64: match binary[4] {
+            ELFCLASS32 => false,
+            ELFCLASS64 => true,
+            class => {
+                return Err(EmbeddedBomReadError::MalformedElf(format!(
+                    "unknown class {class}"
+                )))
+            }
+        },
+        is_big_endian: match binary[5] {
+            ELFDATA2LSB => false,
+            ELFDATA2MSB => true,
+            encoding => {
+                return Err(EmbeddedBomReadError::MalformedElf(format!(
+                    "unknown data encoding {encoding}"
+                )))
+            }
+        },
+    };
+
+    let section_headers_offset = elf.word(0x20, 0x28)?;
+    let (entry_size, count, names_index) = match elf.is_64 {
+        true => (elf.u16(0x3a)?, elf.u16(0x3c)?, elf.u16(0x3e)?),
+        false => (elf.u16(0x2e)?, elf.u16(0x30)?, elf.u16(0x32)?),
+    };
+    let section_header = |index: u64| -> Result<SectionHeader, EmbeddedBomReadError> {
+        // The offsets come from the file, so they may be arbitrarily large
+        let out_of_bounds =
+            || EmbeddedBomReadError::MalformedElf("a section header is out of bounds".to_string());
+        let start = index
+            .checked_mul(u64::from(entry_size))
+            .and_then(|offset| offset.checked_add(section_headers_offset))
+            .ok_or_else(out_of_bounds)?;
+        let field = |offset: u64| start.checked_add(offset).ok_or_else(out_of_bounds);
+        Ok(SectionHeader {
+            name: elf.u32(start)?,
+            section_type: elf.u32(field(4)?)?,
+            offset: elf.word(field(0x10)?, field(0x18)?)?,
+            size: elf.word(field(0x14)?, field(0x20)?)?,
+            link: elf.u32(field(if elf.is_64 { 0x28 } else { 0x18 })?)?,
+        })
+    };
+    if section_headers_offset == 0 {
+        return Ok(None);
+    }
+
+    // Files with a large number of sections store the real values in the first section header
+    let first = section_header(0)?;
+    let count = match count {
+        0 => first.size,
+        count => u64::from(count),
+    };
+    let names_index = match names_index {
+        SHN_XINDEX => u64::from(first.link),
+        index => u64::from(index),
+    };
+
+    let names = section_header(names_index)?;
+    let names = elf.bytes(names.offset, names.size)?;
+    for index in 0..count {
+        let header = section_header(index)?;
+        let section_name = names
+            .get(header.name as usize..)
+            .and_then(|rest| rest.split(|&b| b == 0).next())
+            .ok_or_else(|| {
+                EmbeddedBomReadError::MalformedElf("invalid section name".to_string())
+            })?;
+        if section_name == name.as_bytes() && header.section_type != SHT_NOBITS {
+            return elf.bytes(header.offset, header.size).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds an ELF file with no program headers and the given sections
+    fn build_elf(is_64: bool, is_big_endian: bool, sections: &[(&str, &[u8])]) -> Vec<u8> {
+        let word = |value: usize| -> Vec<u8> {
+            match (is_64, is_big_endian) {
+                (true, true) => (value as u64).to_be_bytes().to_vec(),
+                (true, false) => (value as u64).to_le_bytes().to_vec(),
+                (false, true) => (value as u32).to_be_bytes().to_vec(),
+                (false, false) => (value as u32).to_le_bytes().to_vec(),
+            }
+        };
+        let u16 = |value: usize| -> Vec<u8> {
+            match is_big_endian {
+                true => (value as u16).to_be_bytes().to_vec(),
+                false => (value as u16).to_le_bytes().to_vec(),
+            }
+        };
+        let u32 = |value: usize| -> Vec<u8> {
+            match is_big_endian {
+                true => (value as u32).to_be_bytes().to_vec(),
+                false => (value as u32).to_le_bytes().to_vec(),
+            }
+        };
+        let header_size = if is_64 { 0x40 } else { 0x34 };
+        let entry_size = if is_64 { 0x40 } else { 0x28 };
+
+        // The section names, preceded by the empty name of the null section
+        let mut names = vec![0];
+        let mut name_offsets = Vec::new();
+        for (name, _) in sections.iter().chain([(".shstrtab", &[][..])].iter()) {
+            name_offsets.push(names.len());
+            names.extend_from_slice(name.as_bytes());
+            names.push(0);
+        }
+
+        let mut contents = Vec::new();
+        let mut offsets = Vec::new();
+        for (_, data) in sections.iter() {
+            offsets.push(header_size + contents.len());
+            contents.extend_from_slice(data);
+        }
+        offsets.push(header_size + contents.len());
+        contents.extend_from_slice(&names);
+        let section_headers_offset = header_size + contents.len();
+
+        let mut elf = b"\x7fELF".to_vec();
+        elf.push(if is_64 { ELFCLASS64 } else { ELFCLASS32 });
+        elf.push(if is_big_endian {
+            ELFDATA2MSB
+        } else {
+            ELFDATA2LSB
+        });
+        elf.resize(0x10, 0);
+        elf.extend(u16(2)); // e_type
+        elf.extend(u16(0)); // e_machine
+        elf.extend(u32(1)); // e_version
+        elf.extend(word(0)); // e_entry
+        elf.extend(word(0)); // e_phoff
+        elf.extend(word(section_headers_offset));
+        elf.extend(u32(0)); // e_flags
+        elf.extend(u16(header_size));
+        elf.extend(u16(0)); // e_phentsize
+        elf.extend(u16(0)); // e_phnum
+        elf.extend(u16(entry_size));
+        elf.extend(u16(sections.len() + 2));
+        elf.extend(u16(sections.len() + 1));
+        assert_eq!(elf.len(), header_size);
+        elf.extend(contents);
+
+        elf.extend(vec![0; entry_size]);
+        let sizes = sections
+            .iter()
+            .map(|(_, data)| data.len())
+            .chain([names.len()]);
+        for ((name, offset), size) in name_offsets.into_iter().zip(offsets).zip(sizes) {
+            elf.extend(u32(name));
+            elf.extend(u32(1)); // sh_type
+            elf.extend(word(0)); // sh_flags
+            elf.extend(word(0)); // sh_addr
+            elf.extend(word(offset));
+            elf.extend(word(size));
+            elf.extend(u32(0)); // sh_link
+            elf.extend(u32(0)); // sh_info
+            elf.extend(word(1)); // sh_addralign
+            elf.extend(word(0)); // sh_entsize
+        }
+        elf
+    }
+
+    const BOM: &str = r#"{
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.4",
+        "serialNumber": "urn:uuid:3e671687-395b-41f5-a30f-a58921a69b79",
+        "version": 1
+    }"#;
+
+    #[test]
+    fn it_should_read_the_embedded_bom() {
+        let expected = Bom::parse_from_json(BOM.as_bytes()).unwrap();
+        for (is_64, is_big_endian) in [(true, false), (true, true), (false, false), (false, true)] {
+            let elf = build_elf(
+                is_64,
+                is_big_endian,
+                &[(".text", b"\x90\x90"), (ELF_SECTION_NAME, BOM.as_bytes())],
+            );
+            assert_eq!(read_from_elf(&elf).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn it_should_report_a_missing_bom() {
+        let elf = build_elf(true, false, &[(".text", b"\x90\x90")]);
+        assert!(matches!(
+            read_from_elf(&elf),
+            Err(EmbeddedBomReadError::SectionMissing)
+        ));
+        assert!(matches!(
+            read_from_elf(b"MZ"),
+            Err(EmbeddedBomReadError::NotElf)
+        ));
+        assert!(matches!(
+            read_from_elf(&elf[..0x20]),
+            Err(EmbeddedBomReadError::MalformedElf(_))
+        ));
+    }
+
+    #[test]
+    fn it_should_reject_out_of_bounds_section_headers() {
+        for (is_64, is_big_endian) in [(true, false), (false, true)] {
+            let mut elf = build_elf(is_64, is_big_endian, &[(".text", b"\x90\x90")]);
+            // e_shoff pointing at the end of the address space
+            let (offset, len) = if is_64 { (0x28, 8) } else { (0x20, 4) };
+            elf[offset..offset + len].fill(0xff);
+            assert!(matches!(
+                read_from_elf(&elf),
+                Err(EmbeddedBomReadError::MalformedElf(_))
+            ));
+        }
+    }
+}
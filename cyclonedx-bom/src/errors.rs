@@ -137,3 +137,22 @@ impl XmlReadError {
         }
     }
 }
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum EmbeddedBomReadError {
+    #[error("The file is not an ELF binary")]
+    NotElf,
+
+    #[error("Malformed ELF binary: {0}")]
+    MalformedElf(String),
+
+    #[error("The binary contains no embedded SBOM")]
+    SectionMissing,
+
+    #[error("Failed to read the embedded SBOM: {error}")]
+    JsonReadError {
+        #[from]
+        error: JsonReadError,
+    },
+}
@@ -122,8 +122,12 @@
 //! use cyclonedx_bom::prelude::*;
 //! ```
 
+pub mod cargo_properties;
+pub mod embedded;
 pub mod errors;
 pub mod external_models;
+#[cfg(feature = "license-text")]
+pub mod license_text;
 pub mod models;
 pub mod prelude;
 pub mod schema;
@@ -0,0 +1,215 @@
+/*
+ * This file is part of CycloneDX Rust Cargo.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Identifying licenses from their texts, without network access.
+//!
+//! The text is compared with the reference texts from the [SPDX license list](https://spdx.org/licenses/),
+//! which are bundled into the binary. Both are normalized by lowercasing them, dropping copyright lines
+//! and punctuation, and splitting them into words. The similarity is the
+//! [Sørensen–Dice coefficient](https://en.wikipedia.org/wiki/S%C3%B8rensen%E2%80%93Dice_coefficient)
+//! of the sets of pairs of adjacent words, so it does not depend on how the text is wrapped.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::external_models::spdx::SpdxIdentifier;
+
+/// The confidence below which a match should not be relied upon
+pub const CONFIDENCE_THRESHOLD: f32 = 0.9;
+
+/// The license that is most similar to a text
+#[derive(Clone, Debug, PartialEq)]
+pub struct LicenseMatch {
+    pub identifier: SpdxIdentifier,
+    /// The similarity to the reference text, from 0 to 1
+    pub confidence: f32,
+}
+
+impl LicenseMatch {
+    /// Whether the confidence is at least [`CONFIDENCE_THRESHOLD`]
+    pub fn is_confident(&self) -> bool {
+        self.confidence >= CONFIDENCE_THRESHOLD
+    }
+}
+
+/// The word pairs of every non-deprecated license in the SPDX license list
+static REFERENCE_TEXTS: Lazy<Vec<(&'static str, HashSet<u64>)>> = Lazy::new(|| {
+    spdx::text::LICENSE_TEXTS
+        .iter()
+        .filter(|(id, _)| spdx::license_id(id).is_some_and(|license| !license.is_deprecated()))
+        .map(|(id, text)| (*id, word_pairs(text)))
+        .collect()
+});
+
+/// Finds the SPDX license whose reference text is most similar to the given text.
+///
+/// Returns `None` if the text contains no words in common with any license.
+/// Licenses with identical texts, such as `GPL-3.0-only` and `GPL-3.0-or-later`,
+/// cannot be told apart, in which case the first one in alphabetical order is returned.
+/// ```
+/// use cyclonedx_bom::license_text::identify_license;
+///
+/// let text = "Copyright (c) 2024 Someone
+///
+/// Permission to use, copy, modify, and/or distribute this software for any
+/// purpose with or without fee is hereby granted.
+///
+/// THE SOFTWARE IS PROVIDED \"AS IS\" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+/// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+/// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+/// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+/// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+/// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+/// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.";
+///
+/// let license = identify_license(text).unwrap();
+/// assert_eq!(license.identifier.to_string(), "0BSD");
+/// assert!(license.is_confident());
+/// ```
+pub fn identify_license(text: &str) -> Option<LicenseMatch> {
+    let pairs = word_pairs(text);
+    if pairs.is_empty() {
+        return None;
+    }
+
+    let mut best: Option<(&str, f32)> = None;
+    for (id, reference) in REFERENCE_TEXTS.iter() {
+        let best_confidence = best.map_or(0.0, |(_, confidence)| confidence);
+        // Skip the texts that are too long or too short to beat the best match so far
+        let upper_bound =
+            2.0 * pairs.len().min(reference.len()) as f32 / (pairs.len() + reference.len()) as f32;
+        if upper_bound <= best_confidence {
+            continue;
+        }
+        let confidence = dice_coefficient(&pairs, reference);
+        if confidence > best_confidence {
+            best = Some((id, confidence));
+        }
+    }
+
+    best.map(|(id, confidence)| LicenseMatch {
+        identifier: SpdxIdentifier(id.to_owned()),
+        confidence,
+    })
+}
+
+fn dice_coefficient(a: &HashSet<u64>, b: &HashSet<u64>) -> f32 {
+    let (smaller, larger) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    let common = smaller.iter().filter(|pair| larger.contains(pair)).count();
+    2.0 * common as f32 / (a.len() + b.len()) as f32
+}
+
+/// Hashes of the pairs of adjacent words in the normalized text
+fn word_pairs(text: &str) -> HashSet<u64> {
+    let words = normalize(text);
+    words
+        .windows(2)
+        .map(|pair| {
+            let mut hasher = DefaultHasher::new();
+            pair.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// Matches copyright notices such as `Copyright (c) 2024 ...` or `© Someone`,
+/// but not sentences about copyright such as `copyright owner or ...`
+static COPYRIGHT_LINE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^\W*(copyright\s*(\(c\)|©|\d|\[|<)|©)").unwrap());
+
+/// Splits the text into lowercase words, leaving out punctuation and the lines with copyright notices,
+/// which differ between every copy of a license
+fn normalize(text: &str) -> Vec<String> {
+    text.lines()
+        .filter(|line| !COPYRIGHT_LINE.is_match(line))
+        .flat_map(|line| line.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .map(|word| match word.to_lowercase().as_str() {
+            // British and American spellings are used interchangeably
+            "licence" => "license".to_owned(),
+            "licences" => "licenses".to_owned(),
+            "licenced" => "licensed".to_owned(),
+            word => word.to_owned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    const MIT: &str = r#"MIT License
+
+Copyright (c) 2024 The Example Developers
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+"#;
+
+    #[test]
+    fn it_should_identify_licenses() {
+        let license = identify_license(MIT).unwrap();
+        assert_eq!(license.identifier.to_string(), "MIT");
+        assert!(license.is_confident(), "{}", license.confidence);
+
+        let apache = spdx::license_id("Apache-2.0").unwrap().text();
+        let rewrapped = apache.split_whitespace().collect::<Vec<_>>().join(" ");
+        let license = identify_license(&rewrapped).unwrap();
+        assert_eq!(license.identifier.to_string(), "Apache-2.0");
+        assert!(license.confidence > 0.99, "{}", license.confidence);
+    }
+
+    #[test]
+    fn it_should_not_be_confident_about_other_texts() {
+        let license =
+            identify_license("All rights reserved. Do not redistribute without permission.");
+        assert!(!license.is_some_and(|l| l.is_confident()));
+        assert_eq!(identify_license(""), None);
+    }
+
+    #[test]
+    fn it_should_normalize_texts() {
+        assert_eq!(
+            normalize(
+                "Copyright (c) 2024 Someone\n© Someone\ncopyright owner: This Licence, v2.0."
+            ),
+            ["copyright", "owner", "this", "license", "v2", "0"]
+        );
+    }
+}
@@ -63,6 +63,7 @@ impl Validate for Annotation {
 }
 
 /// Represents an Annotator: organization, individual, component or service.
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Annotator {
     Organization(OrganizationalEntity),
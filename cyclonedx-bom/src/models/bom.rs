@@ -45,10 +45,13 @@ use crate::xml::{FromXmlDocument, ToXml};
 use super::vulnerability::Vulnerability;
 
 /// Represents the spec version of a BOM.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, PartialOrd, strum::Display)]
+#[derive(
+    Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, PartialOrd, strum::Display,
+)]
 #[repr(u16)]
 #[non_exhaustive]
 pub enum SpecVersion {
+    #[default]
     #[strum(to_string = "1.3")]
     #[serde(rename = "1.3")]
     V1_3 = 1,
@@ -60,12 +63,6 @@ pub enum SpecVersion {
     V1_5 = 3,
 }
 
-impl Default for SpecVersion {
-    fn default() -> Self {
-        Self::V1_3
-    }
-}
-
 impl FromStr for SpecVersion {
     type Err = BomError;
 
@@ -102,6 +99,12 @@ impl BomReference {
     }
 }
 
+impl AsRef<str> for BomReference {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Bom {
     pub version: u32,
@@ -142,7 +145,7 @@ impl Bom {
             match SpecVersion::from_str(version)? {
                 SpecVersion::V1_3 => Ok(crate::specs::v1_3::bom::Bom::deserialize(json)?.into()),
                 SpecVersion::V1_4 => Ok(crate::specs::v1_4::bom::Bom::deserialize(json)?.into()),
-                _ => Err(BomError::UnsupportedSpecVersion(version.to_string()).into()),
+                SpecVersion::V1_5 => Ok(crate::specs::v1_5::bom::Bom::deserialize(json)?.into()),
             }
         } else {
             Err(BomError::UnsupportedSpecVersion("No field 'specVersion' found".to_string()).into())
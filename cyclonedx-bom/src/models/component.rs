@@ -593,7 +593,7 @@ pub fn validate_copyright(_copyright: &Copyright) -> Result<(), ValidationError>
 pub struct Copyright(pub String);
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub struct CopyrightTexts(pub(crate) Vec<Copyright>);
+pub struct CopyrightTexts(pub Vec<Copyright>);
 
 impl Validate for CopyrightTexts {
     fn validate_version(&self, _version: SpecVersion) -> ValidationResult {
@@ -3,7 +3,7 @@ use crate::{
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub(crate) enum ResourceReference {
+pub enum ResourceReference {
     Ref(String),
     ExternalReference(ExternalReference),
 }
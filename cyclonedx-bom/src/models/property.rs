@@ -31,6 +31,30 @@ use super::bom::SpecVersion;
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Properties(pub Vec<Property>);
 
+impl Properties {
+    /// Returns the value of the first property with the given name
+    /// ```
+    /// use cyclonedx_bom::models::property::{Properties, Property};
+    ///
+    /// let properties = Properties(vec![Property::new("Foo", "Bar")]);
+    /// assert_eq!(properties.value("Foo"), Some("Bar"));
+    /// ```
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|property| property.name == name)
+            .map(|property| property.value.as_ref())
+    }
+
+    /// Returns the values of all properties with the given name, in order
+    pub fn values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.0
+            .iter()
+            .filter(move |property| property.name == name)
+            .map(|property| property.value.as_ref())
+    }
+}
+
 impl Validate for Properties {
     fn validate_version(&self, version: SpecVersion) -> ValidationResult {
         ValidationContext::new()
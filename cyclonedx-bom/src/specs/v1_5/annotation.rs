@@ -158,6 +158,7 @@ impl From<Annotation> for models::annotation::Annotation {
 }
 
 /// Represents the 'Annotator' field, see https://cyclonedx.org/docs/1.5/json/#annotations_items_annotator
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub(crate) enum Annotator {
@@ -742,7 +742,7 @@ pub(crate) mod test {
         let end_document = event_reader.next().expect("Expected to end the document");
 
         match end_document {
-            reader::XmlEvent::EndDocument { .. } => (),
+            reader::XmlEvent::EndDocument => (),
             other => panic!("Expected to end a document, but got {:?}", other),
         }
 
@@ -773,7 +773,7 @@ pub(crate) mod test {
         let end_document = event_reader.next().expect("Expected to end the document");
 
         match end_document {
-            reader::XmlEvent::EndDocument { .. } => (),
+            reader::XmlEvent::EndDocument => (),
             other => panic!("Expected to end a document, but got {:?}", other),
         }
 